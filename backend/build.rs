@@ -0,0 +1,40 @@
+use std::{env, process::Command};
+
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+
+/// Embed build metadata as compile-time environment variables read by `utils::build_info`.
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=../.git/HEAD");
+    println!("cargo:rerun-if-changed=../.git/refs");
+    println!("cargo:rerun-if-env-changed=GIT_SHA");
+
+    // CI can pass the SHA explicitly when building without the .git directory (e.g. Docker)
+    let git_sha = env::var("GIT_SHA")
+        .ok()
+        .or_else(|| command_output("git", &["rev-parse", "--short=12", "HEAD"]));
+    if let Some(git_sha) = git_sha {
+        println!("cargo:rustc-env=BUILD_GIT_SHA={git_sha}");
+    }
+
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    if let Some(rustc_version) = command_output(&rustc, &["--version"]) {
+        println!("cargo:rustc-env=BUILD_RUSTC_VERSION={rustc_version}");
+    }
+
+    if let Ok(timestamp) = OffsetDateTime::now_utc().format(&Rfc3339) {
+        println!("cargo:rustc-env=BUILD_TIMESTAMP={timestamp}");
+    }
+}
+
+/// Run a command and return its trimmed stdout, or `None` if it fails or prints nothing.
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = Command::new(program).args(args).output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let stdout = String::from_utf8(output.stdout).ok()?;
+    let stdout = stdout.trim();
+    (!stdout.is_empty()).then(|| stdout.to_string())
+}
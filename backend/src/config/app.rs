@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::utils::env_or_default;
 
-#[derive(Deserialize, Serialize, Debug)]
+#[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct AppConfig {
     /// Interval in milliseconds at which some recurring task or loop should tick.
     /// Defaults to `1000` if not set.
@@ -12,29 +12,110 @@ pub struct AppConfig {
     /// Port on which the server should listen.
     /// Defaults to `3000` if not set.
     #[serde(default)]
-    pub port: i32,
+    pub port: u16,
 
     /// Application environment (e.g., "development", "production").
     /// Defaults to "development" if not set.
     #[serde(default)]
     pub environment: String,
+
+    /// Whether the `/admin` operator endpoints are mounted.
+    /// Defaults to `false` if not set.
+    #[serde(default)]
+    pub enable_admin: bool,
+
+    /// Bearer token the `/admin` endpoints require; they reject every request while it is empty.
+    /// Defaults to empty if not set.
+    #[serde(default)]
+    pub admin_token: String,
+
+    /// Seconds allowed for migrations and seeding before the process gives up.
+    /// Defaults to `300` if not set.
+    #[serde(default)]
+    pub startup_deadline_secs: u64,
+
+    /// Comma-separated path prefixes under `/api` that accept non-JSON request bodies.
+    /// Defaults to none if not set.
+    #[serde(default)]
+    pub json_content_type_exempt_paths: String,
+
+    /// Value of the `X-Frame-Options` response header, omitted when empty.
+    /// Defaults to `DENY` if not set.
+    #[serde(default)]
+    pub x_frame_options: String,
+
+    /// `max-age` of the `Strict-Transport-Security` header sent in production, `0` omits it.
+    /// Defaults to one year if not set.
+    #[serde(default)]
+    pub hsts_max_age_secs: u64,
+
+    /// Whether 404 responses suggest the closest known route.
+    /// Defaults to `true` outside of production if not set.
+    #[serde(default)]
+    pub route_suggestions: bool,
+
+    /// Whether startup waits for the database to come up before connecting.
+    /// Defaults to `true` in development if not set.
+    #[serde(default)]
+    pub wait_for_dependencies: bool,
+
+    /// Seconds each dependency gets to come up when waiting for dependencies.
+    /// Defaults to `60` if not set.
+    #[serde(default)]
+    pub dependency_timeout_secs: u64,
+
+    /// Seconds each shutdown step, such as draining in-flight requests, gets before it is
+    /// abandoned. Defaults to `30` if not set.
+    #[serde(default)]
+    pub shutdown_timeout_secs: u64,
+}
+
+impl AppConfig {
+    /// Parsed [`AppConfig::json_content_type_exempt_paths`].
+    pub fn json_content_type_exempt_paths(&self) -> Vec<String> {
+        self.json_content_type_exempt_paths
+            .split(',')
+            .map(str::trim)
+            .filter(|path| !path.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
 }
 
 impl Default for AppConfig {
     fn default() -> Self {
+        let environment = super::environment();
+
         Self {
             host: env_or_default("HOST", "0.0.0.0".to_string()),
             port: env_or_default("PORT", 3000),
-            environment: env_or_default("ENVIRONMENT", "development".to_string()),
+            route_suggestions: env_or_default("ROUTE_SUGGESTIONS", environment != "production"),
+            wait_for_dependencies: env_or_default(
+                "WAIT_FOR_DEPENDENCIES",
+                environment == "development",
+            ),
+            dependency_timeout_secs: env_or_default("DEPENDENCY_TIMEOUT_SECS", 60),
+            shutdown_timeout_secs: env_or_default("SHUTDOWN_TIMEOUT_SECS", 30),
+            environment,
+            enable_admin: env_or_default("ENABLE_ADMIN", false),
+            admin_token: env_or_default("ADMIN_TOKEN", String::new()),
+            startup_deadline_secs: env_or_default("STARTUP_DEADLINE_SECS", 300),
+            json_content_type_exempt_paths: env_or_default(
+                "JSON_CONTENT_TYPE_EXEMPT_PATHS",
+                String::new(),
+            ),
+            x_frame_options: env_or_default("X_FRAME_OPTIONS", "DENY".to_string()),
+            hsts_max_age_secs: env_or_default("HSTS_MAX_AGE_SECS", 31_536_000),
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
     use serial_test::serial;
 
+    use super::*;
+
     #[test]
     #[serial]
     fn test_default_values() {
@@ -47,10 +128,67 @@ mod tests {
         unsafe {
             std::env::remove_var("ENVIRONMENT");
         }
+        unsafe {
+            std::env::remove_var("ENABLE_ADMIN");
+        }
+        unsafe {
+            std::env::remove_var("ADMIN_TOKEN");
+        }
+        unsafe {
+            std::env::remove_var("STARTUP_DEADLINE_SECS");
+        }
+        unsafe {
+            std::env::remove_var("JSON_CONTENT_TYPE_EXEMPT_PATHS");
+        }
+        unsafe {
+            std::env::remove_var("X_FRAME_OPTIONS");
+        }
+        unsafe {
+            std::env::remove_var("HSTS_MAX_AGE_SECS");
+        }
+        unsafe {
+            std::env::remove_var("ROUTE_SUGGESTIONS");
+        }
+        unsafe {
+            std::env::remove_var("WAIT_FOR_DEPENDENCIES");
+        }
+        unsafe {
+            std::env::remove_var("DEPENDENCY_TIMEOUT_SECS");
+        }
+        unsafe {
+            std::env::remove_var("SHUTDOWN_TIMEOUT_SECS");
+        }
         let cfg = AppConfig::default();
         assert_eq!(cfg.host, "0.0.0.0");
         assert_eq!(cfg.port, 3000);
         assert_eq!(cfg.environment, "development");
+        assert!(!cfg.enable_admin);
+        assert!(cfg.admin_token.is_empty());
+        assert_eq!(cfg.startup_deadline_secs, 300);
+        assert!(cfg.json_content_type_exempt_paths().is_empty());
+        assert_eq!(cfg.x_frame_options, "DENY");
+        assert_eq!(cfg.hsts_max_age_secs, 31_536_000);
+        assert!(cfg.route_suggestions);
+        assert!(cfg.wait_for_dependencies);
+        assert_eq!(cfg.dependency_timeout_secs, 60);
+        assert_eq!(cfg.shutdown_timeout_secs, 30);
+    }
+
+    #[test]
+    #[serial]
+    fn test_route_suggestions_are_off_in_production() {
+        unsafe {
+            std::env::set_var("ENVIRONMENT", "production");
+        }
+        unsafe {
+            std::env::remove_var("ROUTE_SUGGESTIONS");
+        }
+        let cfg = AppConfig::default();
+        assert!(!cfg.route_suggestions);
+        assert!(!cfg.wait_for_dependencies);
+        unsafe {
+            std::env::remove_var("ENVIRONMENT");
+        }
     }
 
     #[test]
@@ -65,10 +203,50 @@ mod tests {
         unsafe {
             std::env::set_var("ENVIRONMENT", "prod");
         }
+        unsafe {
+            std::env::set_var("ENABLE_ADMIN", "true");
+        }
+        unsafe {
+            std::env::set_var("ADMIN_TOKEN", "s3cret");
+        }
+        unsafe {
+            std::env::set_var("STARTUP_DEADLINE_SECS", "60");
+        }
+        unsafe {
+            std::env::set_var("JSON_CONTENT_TYPE_EXEMPT_PATHS", "/api/uploads, /api/import");
+        }
+        unsafe {
+            std::env::set_var("X_FRAME_OPTIONS", "SAMEORIGIN");
+        }
+        unsafe {
+            std::env::set_var("HSTS_MAX_AGE_SECS", "0");
+        }
+        unsafe {
+            std::env::set_var("ROUTE_SUGGESTIONS", "false");
+        }
+        unsafe {
+            std::env::set_var("WAIT_FOR_DEPENDENCIES", "true");
+        }
+        unsafe {
+            std::env::set_var("DEPENDENCY_TIMEOUT_SECS", "5");
+        }
+        unsafe {
+            std::env::set_var("SHUTDOWN_TIMEOUT_SECS", "10");
+        }
         let cfg = AppConfig::default();
         assert_eq!(cfg.host, "127.0.0.1");
         assert_eq!(cfg.port, 4321);
         assert_eq!(cfg.environment, "prod");
+        assert!(cfg.enable_admin);
+        assert_eq!(cfg.admin_token, "s3cret");
+        assert_eq!(cfg.startup_deadline_secs, 60);
+        assert_eq!(cfg.json_content_type_exempt_paths(), vec!["/api/uploads", "/api/import"]);
+        assert_eq!(cfg.x_frame_options, "SAMEORIGIN");
+        assert_eq!(cfg.hsts_max_age_secs, 0);
+        assert!(!cfg.route_suggestions);
+        assert!(cfg.wait_for_dependencies);
+        assert_eq!(cfg.dependency_timeout_secs, 5);
+        assert_eq!(cfg.shutdown_timeout_secs, 10);
         unsafe {
             std::env::remove_var("HOST");
         }
@@ -78,5 +256,35 @@ mod tests {
         unsafe {
             std::env::remove_var("ENVIRONMENT");
         }
+        unsafe {
+            std::env::remove_var("ENABLE_ADMIN");
+        }
+        unsafe {
+            std::env::remove_var("ADMIN_TOKEN");
+        }
+        unsafe {
+            std::env::remove_var("STARTUP_DEADLINE_SECS");
+        }
+        unsafe {
+            std::env::remove_var("JSON_CONTENT_TYPE_EXEMPT_PATHS");
+        }
+        unsafe {
+            std::env::remove_var("X_FRAME_OPTIONS");
+        }
+        unsafe {
+            std::env::remove_var("HSTS_MAX_AGE_SECS");
+        }
+        unsafe {
+            std::env::remove_var("ROUTE_SUGGESTIONS");
+        }
+        unsafe {
+            std::env::remove_var("WAIT_FOR_DEPENDENCIES");
+        }
+        unsafe {
+            std::env::remove_var("DEPENDENCY_TIMEOUT_SECS");
+        }
+        unsafe {
+            std::env::remove_var("SHUTDOWN_TIMEOUT_SECS");
+        }
     }
 }
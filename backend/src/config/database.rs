@@ -1,8 +1,8 @@
 use serde::{Deserialize, Serialize};
 
-use crate::utils::env_or_default;
+use crate::{config::lookup, utils::env_or_default};
 
-#[derive(Deserialize, Debug, Serialize)]
+#[derive(Deserialize, Debug, Serialize, Clone)]
 pub struct DatabaseConfig {
     /// The URL for your database connection. Must be set via `DATABASE_URL`.
     pub url: String,
@@ -10,6 +10,19 @@ pub struct DatabaseConfig {
     /// Maximum number of database connetions the application will handle.
     /// Defaults to `5` if not present in the environment.
     pub max_connections: u32,
+
+    /// Number of times a failed initial database connection is retried before giving up.
+    /// Defaults to `5` if not present in the environment.
+    pub connect_retries: u32,
+
+    /// URL of a read replica used for read-only queries, set via `DATABASE_REPLICA_URL`.
+    /// Reads go to the primary database if not set.
+    #[serde(default)]
+    pub replica_url: Option<String>,
+
+    /// Attempts a transaction gets when it fails on a deadlock or serialization conflict.
+    /// Defaults to `3` if not present in the environment.
+    pub txn_max_attempts: u32,
 }
 
 impl Default for DatabaseConfig {
@@ -17,14 +30,20 @@ impl Default for DatabaseConfig {
         Self {
             url: env_or_default("DATABASE_URL", "0.0.0.0".to_string()),
             max_connections: env_or_default("MAX_DATABASE_CONNECTIONS", 5),
+            connect_retries: env_or_default("DB_CONNECT_RETRIES", 5),
+            replica_url: lookup("DATABASE_REPLICA_URL").filter(|url| !url.is_empty()),
+            txn_max_attempts: env_or_default("DB_TXN_MAX_ATTEMPTS", 3),
         }
     }
 }
 #[cfg(test)]
 mod tests {
+    use serial_test::serial;
+
     use super::*;
 
     #[test]
+    #[serial]
     fn test_defaults() {
         unsafe {
             std::env::remove_var("DATABASE_URL");
@@ -32,8 +51,41 @@ mod tests {
         unsafe {
             std::env::remove_var("MAX_DATABASE_CONNECTIONS");
         }
+        unsafe {
+            std::env::remove_var("DB_CONNECT_RETRIES");
+        }
+        unsafe {
+            std::env::remove_var("DATABASE_REPLICA_URL");
+        }
+        unsafe {
+            std::env::remove_var("DB_TXN_MAX_ATTEMPTS");
+        }
         let cfg = DatabaseConfig::default();
         assert_eq!(cfg.url, "0.0.0.0");
         assert_eq!(cfg.max_connections, 5);
+        assert_eq!(cfg.connect_retries, 5);
+        assert_eq!(cfg.replica_url, None);
+        assert_eq!(cfg.txn_max_attempts, 3);
+    }
+
+    #[test]
+    #[serial]
+    fn test_replica_url() {
+        unsafe {
+            std::env::set_var("DATABASE_REPLICA_URL", "mysql://replica/template_service");
+        }
+        assert_eq!(
+            DatabaseConfig::default().replica_url.as_deref(),
+            Some("mysql://replica/template_service")
+        );
+
+        unsafe {
+            std::env::set_var("DATABASE_REPLICA_URL", "");
+        }
+        assert_eq!(DatabaseConfig::default().replica_url, None);
+
+        unsafe {
+            std::env::remove_var("DATABASE_REPLICA_URL");
+        }
     }
 }
@@ -1,6 +1,6 @@
 use serde::{Deserialize, Serialize};
 
-use crate::utils::env_or_default;
+use crate::{config::lookup, utils::env_or_default};
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct LoggingConfig {
@@ -9,11 +9,17 @@ pub struct LoggingConfig {
     #[serde(default)]
     pub level: String,
 
-    /// Log format: "json" for structured logs (Kibana), "pretty" for human-readable
-    /// Defaults to "json" for production use with Kibana.
+    /// Log format: "json" for Bunyan structured logs (Kibana), "json-compact" for flat JSON lines,
+    /// "pretty" for human-readable
+    /// Defaults to "pretty" in the "development" environment and "json" everywhere else.
     #[serde(default)]
     pub format: String,
 
+    /// Field layout of "json" logs: "bunyan", or "ecs" for the Elastic Common Schema
+    /// Defaults to "bunyan" if not set.
+    #[serde(default)]
+    pub schema: String,
+
     /// Service name to include in logs for identification in Kibana
     /// Defaults to "template-service" if not set.
     #[serde(default)]
@@ -23,38 +29,131 @@ pub struct LoggingConfig {
     /// Defaults to "production" if not set.
     #[serde(default)]
     pub environment: String,
+
+    /// Requests taking longer than this many milliseconds are logged as a warning.
+    /// Defaults to `1000` if not set. `0` disables slow request logging.
+    #[serde(default)]
+    pub slow_request_ms: u64,
+
+    /// Fraction (`0.0`-`1.0`) of successful requests that get an access log event.
+    /// Defaults to `1.0` if not set. Non-2xx responses are always logged.
+    #[serde(default)]
+    pub access_log_sample_rate: f64,
+
+    /// Whether request and response bodies are logged. Meant for debugging only.
+    /// Defaults to `false` if not set.
+    #[serde(default)]
+    pub log_bodies: bool,
+
+    /// Logged bodies are truncated to this many bytes.
+    /// Defaults to `4096` if not set.
+    #[serde(default)]
+    pub log_body_max_bytes: usize,
+
+    /// Comma-separated JSON fields whose values are redacted from logged bodies.
+    /// Defaults to `password,token,apiKey` if not set.
+    #[serde(default)]
+    pub log_redact_fields: String,
+
+    /// Sentry DSN used to report panics and internal errors when built with the `sentry` feature.
+    /// Error reporting to Sentry is disabled if not set.
+    #[serde(default)]
+    pub sentry_dsn: Option<String>,
+}
+
+impl LoggingConfig {
+    /// Parsed [`LoggingConfig::log_redact_fields`].
+    pub fn log_redact_fields(&self) -> Vec<String> {
+        self.log_redact_fields
+            .split(',')
+            .map(str::trim)
+            .filter(|field| !field.is_empty())
+            .map(str::to_string)
+            .collect()
+    }
 }
 
 impl Default for LoggingConfig {
     fn default() -> Self {
+        let environment = super::environment();
+        let default_format = match environment.as_str() {
+            | "development" => "pretty",
+            | _ => "json",
+        };
+
         Self {
             level: env_or_default("LOG_LEVEL", "info".to_string()),
-            format: env_or_default("LOG_FORMAT", "json".to_string()),
+            format: env_or_default("LOG_FORMAT", default_format.to_string()),
+            schema: env_or_default("LOG_SCHEMA", "bunyan".to_string()),
             service_name: env_or_default("SERVICE_NAME", "template-service".to_string()),
-            environment: env_or_default("ENVIRONMENT", "production".to_string()),
+            environment,
+            slow_request_ms: env_or_default("SLOW_REQUEST_MS", 1000),
+            access_log_sample_rate: env_or_default("ACCESS_LOG_SAMPLE_RATE", 1.0),
+            log_bodies: env_or_default("LOG_BODIES", false),
+            log_body_max_bytes: env_or_default("LOG_BODY_MAX_BYTES", 4096),
+            log_redact_fields: env_or_default(
+                "LOG_REDACT_FIELDS",
+                "password,token,apiKey".to_string(),
+            ),
+            sentry_dsn: lookup("SENTRY_DSN").filter(|dsn| !dsn.is_empty()),
         }
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::*;
     use serial_test::serial;
 
+    use super::*;
+
     #[test]
     #[serial]
     fn test_default_values() {
         unsafe {
             std::env::remove_var("LOG_LEVEL");
             std::env::remove_var("LOG_FORMAT");
+            std::env::remove_var("LOG_SCHEMA");
             std::env::remove_var("SERVICE_NAME");
             std::env::remove_var("ENVIRONMENT");
+            std::env::remove_var("SLOW_REQUEST_MS");
+            std::env::remove_var("ACCESS_LOG_SAMPLE_RATE");
+            std::env::remove_var("LOG_BODIES");
+            std::env::remove_var("LOG_BODY_MAX_BYTES");
+            std::env::remove_var("LOG_REDACT_FIELDS");
+            std::env::remove_var("SENTRY_DSN");
         }
         let cfg = LoggingConfig::default();
         assert_eq!(cfg.level, "info");
-        assert_eq!(cfg.format, "json");
+        assert_eq!(cfg.format, "pretty");
+        assert_eq!(cfg.schema, "bunyan");
         assert_eq!(cfg.service_name, "template-service");
-        assert_eq!(cfg.environment, "production");
+        assert_eq!(cfg.environment, "development");
+        assert_eq!(cfg.slow_request_ms, 1000);
+        assert_eq!(cfg.access_log_sample_rate, 1.0);
+        assert!(!cfg.log_bodies);
+        assert_eq!(cfg.log_body_max_bytes, 4096);
+        assert_eq!(cfg.log_redact_fields(), vec!["password", "token", "apiKey"]);
+        assert_eq!(cfg.sentry_dsn, None);
+    }
+
+    #[test]
+    #[serial]
+    fn test_unset_environment_matches_app_config() {
+        unsafe {
+            std::env::remove_var("ENVIRONMENT");
+            std::env::remove_var("LOG_FORMAT");
+        }
+        let cfg = LoggingConfig::default();
+        assert_eq!(cfg.environment, crate::config::AppConfig::default().environment);
+        assert_eq!(cfg.format, "pretty");
+
+        unsafe {
+            std::env::set_var("ENVIRONMENT", "production");
+        }
+        assert_eq!(LoggingConfig::default().format, "json");
+        unsafe {
+            std::env::remove_var("ENVIRONMENT");
+        }
     }
 
     #[test]
@@ -63,19 +162,82 @@ mod tests {
         unsafe {
             std::env::set_var("LOG_LEVEL", "debug");
             std::env::set_var("LOG_FORMAT", "pretty");
+            std::env::set_var("LOG_SCHEMA", "ecs");
             std::env::set_var("SERVICE_NAME", "test-service");
             std::env::set_var("ENVIRONMENT", "development");
+            std::env::set_var("SLOW_REQUEST_MS", "250");
+            std::env::set_var("ACCESS_LOG_SAMPLE_RATE", "0.1");
+            std::env::set_var("LOG_BODIES", "true");
+            std::env::set_var("LOG_BODY_MAX_BYTES", "256");
+            std::env::set_var("LOG_REDACT_FIELDS", "secret, pin");
+            std::env::set_var("SENTRY_DSN", "https://key@sentry.example.com/1");
         }
         let cfg = LoggingConfig::default();
         assert_eq!(cfg.level, "debug");
         assert_eq!(cfg.format, "pretty");
+        assert_eq!(cfg.schema, "ecs");
         assert_eq!(cfg.service_name, "test-service");
         assert_eq!(cfg.environment, "development");
+        assert_eq!(cfg.slow_request_ms, 250);
+        assert_eq!(cfg.access_log_sample_rate, 0.1);
+        assert!(cfg.log_bodies);
+        assert_eq!(cfg.log_body_max_bytes, 256);
+        assert_eq!(cfg.log_redact_fields(), vec!["secret", "pin"]);
+        assert_eq!(cfg.sentry_dsn.as_deref(), Some("https://key@sentry.example.com/1"));
         unsafe {
             std::env::remove_var("LOG_LEVEL");
             std::env::remove_var("LOG_FORMAT");
+            std::env::remove_var("LOG_SCHEMA");
             std::env::remove_var("SERVICE_NAME");
             std::env::remove_var("ENVIRONMENT");
+            std::env::remove_var("SLOW_REQUEST_MS");
+            std::env::remove_var("ACCESS_LOG_SAMPLE_RATE");
+            std::env::remove_var("LOG_BODIES");
+            std::env::remove_var("LOG_BODY_MAX_BYTES");
+            std::env::remove_var("LOG_REDACT_FIELDS");
+            std::env::remove_var("SENTRY_DSN");
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_format_follows_environment() {
+        unsafe {
+            std::env::remove_var("LOG_FORMAT");
+            std::env::set_var("ENVIRONMENT", "development");
+        }
+        assert_eq!(LoggingConfig::default().format, "pretty");
+
+        for environment in ["staging", "production"] {
+            unsafe {
+                std::env::set_var("ENVIRONMENT", environment);
+            }
+            assert_eq!(LoggingConfig::default().format, "json", "{environment}");
+        }
+
+        unsafe {
+            std::env::remove_var("ENVIRONMENT");
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_explicit_format_wins_over_environment() {
+        unsafe {
+            std::env::set_var("ENVIRONMENT", "development");
+            std::env::set_var("LOG_FORMAT", "json");
+        }
+        assert_eq!(LoggingConfig::default().format, "json");
+
+        unsafe {
+            std::env::set_var("ENVIRONMENT", "production");
+            std::env::set_var("LOG_FORMAT", "pretty");
+        }
+        assert_eq!(LoggingConfig::default().format, "pretty");
+
+        unsafe {
+            std::env::remove_var("ENVIRONMENT");
+            std::env::remove_var("LOG_FORMAT");
         }
     }
 }
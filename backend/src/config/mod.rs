@@ -1,15 +1,35 @@
-use app::AppConfig;
-use database::DatabaseConfig;
+pub use app::AppConfig;
+pub use database::DatabaseConfig;
+pub use file::{ConfigFileError, lookup};
+pub use logging::LoggingConfig;
+pub use seeder::{ConflictStrategy, SeederConfig};
 use zirv_config::register_config;
 
-pub use logging::LoggingConfig;
+use crate::utils::env_or_default;
 
 mod app;
 mod database;
+mod file;
 pub mod logging;
+mod seeder;
+
+/// Running environment from `ENVIRONMENT`, shared by every config that depends on it.
+///
+/// Defaults to "development" if not set.
+pub fn environment() -> String {
+    env_or_default("ENVIRONMENT", "development".to_string())
+}
+
+/// Load the optional `CONFIG_FILE` and register every config namespace.
+///
+/// Environment variables take precedence over the file, which takes precedence over the
+/// defaults.
+pub fn register_configs() -> Result<(), ConfigFileError> {
+    file::load_config_file()?;
 
-pub fn register_configs() {
     register_config!("app", AppConfig::default());
     register_config!("database", DatabaseConfig::default());
     register_config!("logging", LoggingConfig::default());
+    register_config!("seeder", SeederConfig::default());
+    Ok(())
 }
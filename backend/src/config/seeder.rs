@@ -0,0 +1,140 @@
+use std::{fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils::env_or_default;
+
+/// What the seeder does when seed rows collide with rows already in the database.
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictStrategy {
+    /// Leave the existing rows alone and continue with the next seed file.
+    #[default]
+    Skip,
+
+    /// Stop seeding and report the conflict as an error.
+    Abort,
+
+    /// Overwrite the existing rows with the seeded values.
+    Upsert,
+}
+
+impl FromStr for ConflictStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            | "skip" => Ok(ConflictStrategy::Skip),
+            | "abort" => Ok(ConflictStrategy::Abort),
+            | "upsert" => Ok(ConflictStrategy::Upsert),
+            | other => Err(format!("unknown seed conflict strategy: {other}")),
+        }
+    }
+}
+
+impl fmt::Display for ConflictStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            | ConflictStrategy::Skip => write!(f, "skip"),
+            | ConflictStrategy::Abort => write!(f, "abort"),
+            | ConflictStrategy::Upsert => write!(f, "upsert"),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct SeederConfig {
+    /// Behavior when seed rows violate a unique constraint: `skip`, `abort` or `upsert`.
+    /// Defaults to `skip` if not set.
+    #[serde(default)]
+    pub conflict_strategy: ConflictStrategy,
+
+    /// Most rows inserted by a single statement; large seed files are split into chunks.
+    /// Defaults to 1000 if not set.
+    #[serde(default)]
+    pub chunk_rows: usize,
+
+    /// Whether a missing seed folder for the environment fails seeding instead of logging a
+    /// warning. Defaults to `false` if not set.
+    #[serde(default)]
+    pub require_env_dir: bool,
+
+    /// Number of inserted rows between two seeding progress log events.
+    /// Defaults to 1000 if not set.
+    #[serde(default)]
+    pub progress_interval: usize,
+}
+
+impl Default for SeederConfig {
+    fn default() -> Self {
+        Self {
+            conflict_strategy: env_or_default("SEED_CONFLICT_STRATEGY", ConflictStrategy::Skip),
+            chunk_rows: env_or_default("SEED_CHUNK_ROWS", 1000),
+            require_env_dir: env_or_default("SEED_REQUIRE_ENV_DIR", false),
+            progress_interval: env_or_default("SEED_PROGRESS_INTERVAL", 1000),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serial_test::serial;
+
+    use super::*;
+
+    #[test]
+    #[serial]
+    fn test_default_values() {
+        unsafe {
+            std::env::remove_var("SEED_CONFLICT_STRATEGY");
+        }
+        unsafe {
+            std::env::remove_var("SEED_CHUNK_ROWS");
+        }
+        unsafe {
+            std::env::remove_var("SEED_REQUIRE_ENV_DIR");
+        }
+        unsafe {
+            std::env::remove_var("SEED_PROGRESS_INTERVAL");
+        }
+        let cfg = SeederConfig::default();
+        assert_eq!(cfg.conflict_strategy, ConflictStrategy::Skip);
+        assert_eq!(cfg.chunk_rows, 1000);
+        assert!(!cfg.require_env_dir);
+        assert_eq!(cfg.progress_interval, 1000);
+    }
+
+    #[test]
+    #[serial]
+    fn test_env_overrides() {
+        unsafe {
+            std::env::set_var("SEED_CONFLICT_STRATEGY", "Upsert");
+        }
+        unsafe {
+            std::env::set_var("SEED_CHUNK_ROWS", "500");
+        }
+        unsafe {
+            std::env::set_var("SEED_REQUIRE_ENV_DIR", "true");
+        }
+        unsafe {
+            std::env::set_var("SEED_PROGRESS_INTERVAL", "250");
+        }
+        let cfg = SeederConfig::default();
+        assert_eq!(cfg.conflict_strategy, ConflictStrategy::Upsert);
+        assert_eq!(cfg.chunk_rows, 500);
+        assert!(cfg.require_env_dir);
+        assert_eq!(cfg.progress_interval, 250);
+        unsafe {
+            std::env::remove_var("SEED_CONFLICT_STRATEGY");
+        }
+        unsafe {
+            std::env::remove_var("SEED_CHUNK_ROWS");
+        }
+        unsafe {
+            std::env::remove_var("SEED_REQUIRE_ENV_DIR");
+        }
+        unsafe {
+            std::env::remove_var("SEED_PROGRESS_INTERVAL");
+        }
+    }
+}
@@ -1,33 +1,252 @@
-use actix_web::{HttpResponse, Responder, get};
+use actix_web::{
+    HttpRequest,
+    HttpResponse,
+    Responder,
+    ResponseError,
+    http::header::ContentType,
+    web,
+};
+use serde_json::json;
+
+use crate::{
+    controllers::responses::ApiError,
+    state::AppState,
+    utils::{build_info::BUILD_INFO, routes::RouteTable},
+};
 
-#[get("/")]
 pub async fn health_check() -> impl Responder {
+    HttpResponse::Ok().json(json!({
+        "status": "ok",
+        "version": BUILD_INFO.version,
+    }))
+}
+
+/// Liveness probe reporting the running version and how long the process has been up.
+pub async fn liveness(state: web::Data<AppState>) -> impl Responder {
+    HttpResponse::Ok().json(json!({
+        "status": "ok",
+        "version": BUILD_INFO.version,
+        "uptime_secs": state.started_at.elapsed().as_secs(),
+    }))
+}
+
+/// Reports ready once migrations and seeding have completed, `503` until then.
+pub async fn readiness(state: web::Data<AppState>) -> impl Responder {
+    match state.readiness.is_ready() {
+        | true => HttpResponse::Ok().json(json!({ "status": "ready" })),
+        | false => HttpResponse::ServiceUnavailable().json(json!({ "status": "starting" })),
+    }
+}
+
+/// Build metadata of the running service.
+pub async fn version() -> impl Responder {
+    HttpResponse::Ok().json(BUILD_INFO)
+}
+
+/// Plaintext `pong` for load balancers, touching no dependency.
+pub async fn ping() -> impl Responder {
     HttpResponse::Ok()
+        .content_type(ContentType::plaintext())
+        .body("pong")
 }
 
-pub async fn not_found() -> impl Responder {
-    HttpResponse::NotFound()
+/// Fallback for unmatched requests.
+///
+/// Answers `405` with an `Allow` header when the path is served under other methods, `404`
+/// otherwise, suggesting the closest route when the [`RouteTable`] has suggestions enabled.
+pub async fn not_found(req: HttpRequest, routes: Option<web::Data<RouteTable>>) -> HttpResponse {
+    let path = req.path();
+    let allowed = routes
+        .as_ref()
+        .map(|routes| routes.allowed_methods(path))
+        .unwrap_or_default();
+
+    let error = match allowed.is_empty() {
+        | false => ApiError::MethodNotAllowed {
+            message: format!("Method {} is not allowed for {path}", req.method()),
+            allowed,
+        },
+        | true => match routes.as_ref().and_then(|routes| routes.suggest(path)) {
+            | Some(suggestion) => {
+                ApiError::NotFound(format!("No route for {path}, did you mean {suggestion}?"))
+            }
+            | None => ApiError::NotFound(format!("No route for {path}")),
+        },
+    };
+
+    error.error_response()
 }
 
 #[cfg(test)]
 mod tests {
+    use actix_web::http::{Method, StatusCode, header};
+
     use super::*;
-    use actix_web::http::StatusCode;
 
     #[actix_rt::test]
     async fn test_health_check() {
-        let app =
-            actix_web::test::init_service(actix_web::App::new().service(super::health_check)).await;
+        let app = actix_web::test::init_service(
+            actix_web::App::new().route("/", web::get().to(super::health_check)),
+        )
+        .await;
         let req = actix_web::test::TestRequest::get().uri("/").to_request();
         let resp = actix_web::test::call_service(&app, req).await;
         assert_eq!(resp.status(), StatusCode::OK);
+
+        let body: serde_json::Value = actix_web::test::read_body_json(resp).await;
+        assert_eq!(body["status"], "ok");
+        assert_eq!(body["version"], env!("CARGO_PKG_VERSION"));
+    }
+
+    #[actix_rt::test]
+    async fn test_liveness_reports_uptime() {
+        let state = web::Data::new(AppState::for_tests());
+        let app = actix_web::test::init_service(
+            actix_web::App::new()
+                .app_data(state)
+                .route("/health/live", web::get().to(super::liveness)),
+        )
+        .await;
+
+        let req = actix_web::test::TestRequest::get()
+            .uri("/health/live")
+            .to_request();
+        let first: serde_json::Value = actix_web::test::call_and_read_body_json(&app, req).await;
+        assert_eq!(first["status"], "ok");
+        assert_eq!(first["version"], env!("CARGO_PKG_VERSION"));
+
+        actix_rt::time::sleep(std::time::Duration::from_millis(1100)).await;
+
+        let req = actix_web::test::TestRequest::get()
+            .uri("/health/live")
+            .to_request();
+        let second: serde_json::Value = actix_web::test::call_and_read_body_json(&app, req).await;
+        assert!(second["uptime_secs"].as_u64() > first["uptime_secs"].as_u64());
+    }
+
+    #[actix_rt::test]
+    async fn test_readiness_transitions() {
+        let state = web::Data::new(AppState::for_tests());
+        let app = actix_web::test::init_service(
+            actix_web::App::new()
+                .app_data(state.clone())
+                .route("/health/ready", web::get().to(super::readiness)),
+        )
+        .await;
+
+        let req = actix_web::test::TestRequest::get()
+            .uri("/health/ready")
+            .to_request();
+        let resp = actix_web::test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+
+        state.readiness.mark_ready();
+
+        let req = actix_web::test::TestRequest::get()
+            .uri("/health/ready")
+            .to_request();
+        let resp = actix_web::test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[actix_rt::test]
+    async fn test_version() {
+        let app = actix_web::test::init_service(
+            actix_web::App::new().route("/version", web::get().to(super::version)),
+        )
+        .await;
+        let req = actix_web::test::TestRequest::get()
+            .uri("/version")
+            .to_request();
+        let resp = actix_web::test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let body: serde_json::Value = actix_web::test::read_body_json(resp).await;
+        assert_eq!(body["version"], env!("CARGO_PKG_VERSION"));
+        for field in ["git_sha", "build_timestamp", "rustc_version"] {
+            assert!(body[field].is_string(), "missing {field}");
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_ping() {
+        let app = actix_web::test::init_service(
+            actix_web::App::new().route("/ping", web::get().to(super::ping)),
+        )
+        .await;
+        let req = actix_web::test::TestRequest::get()
+            .uri("/ping")
+            .to_request();
+        let resp = actix_web::test::call_service(&app, req).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+        assert_eq!(resp.headers().get(header::CONTENT_TYPE).unwrap(), "text/plain; charset=utf-8");
+
+        let body = actix_web::test::read_body(resp).await;
+        assert_eq!(body, "pong");
+    }
+
+    async fn fallback(
+        suggestions: bool,
+        req: actix_web::test::TestRequest,
+    ) -> (StatusCode, Option<String>, serde_json::Value) {
+        let routes = RouteTable::new(
+            vec![(Method::GET, "/templates".to_string()), (Method::POST, "/templates".to_string())],
+            suggestions,
+        );
+        let app = actix_web::test::init_service(
+            actix_web::App::new()
+                .app_data(web::Data::new(routes))
+                .route("/templates", web::get().to(HttpResponse::Ok))
+                .route("/templates", web::post().to(HttpResponse::Created))
+                .default_service(web::route().to(super::not_found)),
+        )
+        .await;
+
+        let resp = actix_web::test::call_service(&app, req.to_request()).await;
+        let status = resp.status();
+        let allow = resp
+            .headers()
+            .get(header::ALLOW)
+            .map(|v| v.to_str().unwrap().to_string());
+        (status, allow, actix_web::test::read_body_json(resp).await)
     }
 
     #[actix_rt::test]
     async fn test_not_found() {
-        let resp = super::not_found()
-            .await
-            .respond_to(&actix_web::test::TestRequest::default().to_http_request());
+        let req = actix_web::test::TestRequest::get().uri("/unknown");
+        let (status, allow, body) = fallback(true, req).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(allow, None);
+        assert_eq!(body["error"], "not_found");
+        assert_eq!(body["message"], "No route for /unknown");
+    }
+
+    #[actix_rt::test]
+    async fn test_wrong_method_is_not_allowed() {
+        let req = actix_web::test::TestRequest::delete().uri("/templates");
+        let (status, allow, body) = fallback(true, req).await;
+        assert_eq!(status, StatusCode::METHOD_NOT_ALLOWED);
+        assert_eq!(allow.as_deref(), Some("GET, POST"));
+        assert_eq!(body["error"], "method_not_allowed");
+        assert_eq!(body["message"], "Method DELETE is not allowed for /templates");
+    }
+
+    #[actix_rt::test]
+    async fn test_not_found_suggests_closest_route() {
+        let req = || actix_web::test::TestRequest::get().uri("/templtes");
+
+        let (status, _, body) = fallback(true, req()).await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(body["message"], "No route for /templtes, did you mean /templates?");
+
+        let (_, _, body) = fallback(false, req()).await;
+        assert_eq!(body["message"], "No route for /templtes");
+    }
+
+    #[actix_rt::test]
+    async fn test_not_found_without_route_table() {
+        let resp =
+            super::not_found(actix_web::test::TestRequest::default().to_http_request(), None).await;
         assert_eq!(resp.status(), StatusCode::NOT_FOUND);
     }
 }
@@ -1,3 +1,4 @@
+pub mod admin;
 pub mod base;
 pub mod requests;
 pub mod responses;
@@ -1 +1,112 @@
+use actix_web::{HttpRequest, error::JsonPayloadError, web};
+pub use validated_json::ValidatedJson;
 
+use crate::controllers::responses::ApiError;
+
+mod validated_json;
+
+/// `web::Json` settings rendering extraction failures as [`ApiError`] bodies.
+///
+/// Handlers should extract [`ValidatedJson`], which also reports the path of a field that fails
+/// to deserialize; plain `web::Json` only forwards serde's message.
+pub fn json_config() -> web::JsonConfig {
+    web::JsonConfig::default().error_handler(json_error_handler)
+}
+
+fn json_error_handler(err: JsonPayloadError, _req: &HttpRequest) -> actix_web::Error {
+    let error = match err {
+        | JsonPayloadError::Deserialize(e) if e.is_data() => {
+            ApiError::BadRequest(format!("Invalid request body: {e}"))
+        }
+        | JsonPayloadError::Deserialize(e) => ApiError::BadRequest(format!("Malformed JSON: {e}")),
+        | JsonPayloadError::ContentType => {
+            ApiError::UnsupportedMediaType("Expected Content-Type application/json".to_string())
+        }
+        | e @ (JsonPayloadError::Overflow {
+            ..
+        }
+        | JsonPayloadError::OverflowKnownLength {
+            ..
+        }) => ApiError::PayloadTooLarge(e.to_string()),
+        | e => ApiError::BadRequest(e.to_string()),
+    };
+
+    error.into()
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        App,
+        HttpResponse,
+        http::{StatusCode, header},
+    };
+    use serde::Deserialize;
+    use validator::Validate;
+
+    use super::*;
+
+    #[derive(Deserialize, Validate)]
+    struct Template {
+        name: String,
+    }
+
+    async fn create(template: ValidatedJson<Template>) -> HttpResponse {
+        HttpResponse::Created().body(template.into_inner().name)
+    }
+
+    async fn post(body: &'static str) -> (StatusCode, serde_json::Value) {
+        let app = actix_web::test::init_service(
+            App::new()
+                .app_data(json_config())
+                .route("/templates", web::post().to(create)),
+        )
+        .await;
+        let req = actix_web::test::TestRequest::post()
+            .uri("/templates")
+            .insert_header((header::CONTENT_TYPE, "application/json"))
+            .set_payload(body)
+            .to_request();
+
+        let resp = actix_web::test::call_service(&app, req).await;
+        let status = resp.status();
+        let body = actix_web::test::read_body(resp).await;
+        (status, serde_json::from_slice(&body).unwrap_or_default())
+    }
+
+    #[actix_rt::test]
+    async fn test_malformed_json_is_structured() {
+        let (status, body) = post(r#"{"name": "#).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["status"], 400);
+        assert_eq!(body["error"], "bad_request");
+        assert!(
+            body["message"]
+                .as_str()
+                .unwrap()
+                .starts_with("Malformed JSON: EOF while parsing")
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_invalid_field_is_reported() {
+        let (status, body) = post(r#"{"name": 42}"#).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["error"], "bad_request");
+        assert_eq!(body["field"], "name");
+        assert_eq!(
+            body["message"],
+            "Invalid request body: invalid type: integer `42`, expected a string"
+        );
+
+        let (_, body) = post("{}").await;
+        assert_eq!(body["message"], "Invalid request body: missing field `name`");
+        assert!(body.get("field").is_none());
+    }
+
+    #[actix_rt::test]
+    async fn test_valid_body_passes() {
+        let (status, _) = post(r#"{"name": "welcome"}"#).await;
+        assert_eq!(status, StatusCode::CREATED);
+    }
+}
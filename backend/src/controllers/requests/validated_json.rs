@@ -0,0 +1,164 @@
+use std::{future::Future, ops::Deref, pin::Pin};
+
+use actix_web::{FromRequest, HttpRequest, dev::Payload, web};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use validator::Validate;
+
+use crate::controllers::responses::ApiError;
+
+/// `web::Json` extractor that also runs the body's [`Validate`] rules.
+///
+/// Invalid bodies are rejected with a `422` [`ApiError::Validation`] listing the violations of
+/// each field. Bodies that don't deserialize into `T` are rejected with a `400`
+/// [`ApiError::InvalidBody`] naming the failing field. Malformed JSON, a wrong content type and
+/// oversized bodies are reported as usual through [`json_config`](super::json_config).
+///
+/// ```ignore
+/// #[derive(Deserialize, Validate)]
+/// struct CreateTemplate {
+///     #[validate(length(min = 1, max = 100))]
+///     name: String,
+///     #[validate(email)]
+///     owner_email: String,
+/// }
+///
+/// async fn create(template: ValidatedJson<CreateTemplate>) -> impl Responder { ... }
+/// ```
+#[derive(Debug)]
+pub struct ValidatedJson<T>(pub T);
+
+impl<T> ValidatedJson<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Deref for ValidatedJson<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> FromRequest for ValidatedJson<T>
+where
+    T: DeserializeOwned + Validate + 'static,
+{
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self, Self::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let json = web::Json::<Value>::from_request(req, payload);
+
+        Box::pin(async move {
+            let value: T = deserialize(json.await?.into_inner())?;
+            value.validate().map_err(ApiError::from)?;
+            Ok(ValidatedJson(value))
+        })
+    }
+}
+
+/// Deserialize a parsed body, keeping the path of the field that failed.
+fn deserialize<T: DeserializeOwned>(body: Value) -> Result<T, ApiError> {
+    serde_path_to_error::deserialize(body).map_err(|e| {
+        let field = match e.path().iter().next() {
+            | Some(_) => Some(e.path().to_string()),
+            | None => None,
+        };
+        ApiError::InvalidBody {
+            message: format!("Invalid request body: {}", e.inner()),
+            field,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use actix_web::{
+        App,
+        HttpResponse,
+        http::{StatusCode, header},
+    };
+    use serde::Deserialize;
+    use serde_json::json;
+
+    use super::*;
+    use crate::controllers::requests::json_config;
+
+    #[derive(Deserialize, Validate)]
+    struct CreateTemplate {
+        #[validate(length(min = 1, max = 20))]
+        name: String,
+        #[validate(email(message = "must be an email address"))]
+        owner_email: String,
+    }
+
+    async fn create(template: ValidatedJson<CreateTemplate>) -> HttpResponse {
+        HttpResponse::Created().body(template.into_inner().name)
+    }
+
+    async fn post(body: serde_json::Value) -> (StatusCode, actix_web::web::Bytes) {
+        let app = actix_web::test::init_service(
+            App::new()
+                .app_data(json_config())
+                .route("/templates", web::post().to(create)),
+        )
+        .await;
+        let req = actix_web::test::TestRequest::post()
+            .uri("/templates")
+            .insert_header((header::CONTENT_TYPE, "application/json"))
+            .set_payload(body.to_string())
+            .to_request();
+
+        let resp = actix_web::test::call_service(&app, req).await;
+        (resp.status(), actix_web::test::read_body(resp).await)
+    }
+
+    #[actix_rt::test]
+    async fn test_valid_payload_passes() {
+        let (status, body) =
+            post(json!({ "name": "welcome", "owner_email": "ada@example.com" })).await;
+        assert_eq!(status, StatusCode::CREATED);
+        assert_eq!(body, "welcome");
+    }
+
+    #[actix_rt::test]
+    async fn test_invalid_payload_lists_field_violations() {
+        let (status, body) = post(json!({ "name": "", "owner_email": "not an email" })).await;
+        assert_eq!(status, StatusCode::UNPROCESSABLE_ENTITY);
+
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body,
+            json!({
+                "status": 422,
+                "error": "validation_failed",
+                "message": "Request validation failed",
+                "fields": {
+                    "name": ["length"],
+                    "owner_email": ["must be an email address"],
+                },
+            })
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_deserialization_errors_are_not_validation_errors() {
+        let (status, _) = post(json!({ "name": "welcome" })).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn test_deserialization_errors_name_the_field() {
+        let body = json!({ "emails": ["ada@example.com", 42] });
+        let err = deserialize::<BTreeMap<String, Vec<String>>>(body).unwrap_err();
+        assert!(matches!(
+            err,
+            ApiError::InvalidBody { field: Some(ref field), .. } if field == "emails[1]"
+        ));
+    }
+}
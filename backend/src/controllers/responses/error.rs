@@ -0,0 +1,386 @@
+use std::{collections::BTreeMap, fmt};
+
+use actix_web::{
+    HttpResponse,
+    ResponseError,
+    http::{Method, StatusCode, header},
+};
+use serde::Serialize;
+use sqlx::error::ErrorKind;
+use validator::{ValidationErrors, ValidationErrorsKind};
+
+use crate::utils::{
+    database::TransactionError,
+    errors::{capture_message, current_request_id},
+};
+
+/// Error returned by handlers and middleware, rendered as a structured JSON body.
+///
+/// ```json
+/// { "status": 415, "error": "unsupported_media_type", "message": "..." }
+/// ```
+///
+/// Database errors additionally carry the violated `constraint` when the driver reports it,
+/// request bodies of the wrong shape the path of the failing `field`, and validation errors list
+/// the violations of each field under `fields`.
+#[derive(Debug)]
+pub enum ApiError {
+    BadRequest(String),
+    /// Request body that doesn't deserialize, with the path of the failing field such as
+    /// `owner.email` unless the body itself is at fault.
+    InvalidBody {
+        message: String,
+        field: Option<String>,
+    },
+    /// Missing or wrong credentials, sent with a `WWW-Authenticate: Bearer` header.
+    Unauthorized(String),
+    NotFound(String),
+    /// None of the response types listed in `Accept` can be produced.
+    NotAcceptable(String),
+    /// Sent with an `Allow` header listing `allowed`.
+    MethodNotAllowed {
+        message: String,
+        allowed: Vec<Method>,
+    },
+    Conflict {
+        message: String,
+        constraint: Option<String>,
+    },
+    UnprocessableEntity {
+        message: String,
+        constraint: Option<String>,
+    },
+    /// Field path -> violated rules, such as `{ "email": ["email"] }`.
+    Validation(BTreeMap<String, Vec<String>>),
+    PayloadTooLarge(String),
+    UnsupportedMediaType(String),
+    Internal(String),
+}
+
+#[derive(Serialize)]
+struct ErrorBody<'a> {
+    status: u16,
+    error: &'a str,
+    message: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    constraint: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    field: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    fields: Option<&'a BTreeMap<String, Vec<String>>>,
+}
+
+impl ApiError {
+    /// Machine-readable error code.
+    fn code(&self) -> &'static str {
+        match self {
+            | ApiError::BadRequest(_)
+            | ApiError::InvalidBody {
+                ..
+            } => "bad_request",
+            | ApiError::Unauthorized(_) => "unauthorized",
+            | ApiError::NotFound(_) => "not_found",
+            | ApiError::NotAcceptable(_) => "not_acceptable",
+            | ApiError::MethodNotAllowed {
+                ..
+            } => "method_not_allowed",
+            | ApiError::Conflict {
+                ..
+            } => "conflict",
+            | ApiError::UnprocessableEntity {
+                ..
+            } => "unprocessable_entity",
+            | ApiError::Validation(_) => "validation_failed",
+            | ApiError::PayloadTooLarge(_) => "payload_too_large",
+            | ApiError::UnsupportedMediaType(_) => "unsupported_media_type",
+            | ApiError::Internal(_) => "internal_error",
+        }
+    }
+
+    fn message(&self) -> &str {
+        match self {
+            | ApiError::BadRequest(message)
+            | ApiError::InvalidBody {
+                message, ..
+            }
+            | ApiError::Unauthorized(message)
+            | ApiError::NotFound(message)
+            | ApiError::NotAcceptable(message)
+            | ApiError::MethodNotAllowed {
+                message, ..
+            }
+            | ApiError::Conflict {
+                message, ..
+            }
+            | ApiError::UnprocessableEntity {
+                message, ..
+            }
+            | ApiError::PayloadTooLarge(message)
+            | ApiError::UnsupportedMediaType(message)
+            | ApiError::Internal(message) => message,
+            | ApiError::Validation(_) => "Request validation failed",
+        }
+    }
+
+    fn constraint(&self) -> Option<&str> {
+        match self {
+            | ApiError::Conflict {
+                constraint, ..
+            }
+            | ApiError::UnprocessableEntity {
+                constraint, ..
+            } => constraint.as_deref(),
+            | _ => None,
+        }
+    }
+
+    fn field(&self) -> Option<&str> {
+        match self {
+            | ApiError::InvalidBody {
+                field, ..
+            } => field.as_deref(),
+            | _ => None,
+        }
+    }
+
+    fn fields(&self) -> Option<&BTreeMap<String, Vec<String>>> {
+        match self {
+            | ApiError::Validation(fields) => Some(fields),
+            | _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.code(), self.message())
+    }
+}
+
+impl ResponseError for ApiError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            | ApiError::BadRequest(_)
+            | ApiError::InvalidBody {
+                ..
+            } => StatusCode::BAD_REQUEST,
+            | ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            | ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            | ApiError::NotAcceptable(_) => StatusCode::NOT_ACCEPTABLE,
+            | ApiError::MethodNotAllowed {
+                ..
+            } => StatusCode::METHOD_NOT_ALLOWED,
+            | ApiError::Conflict {
+                ..
+            } => StatusCode::CONFLICT,
+            | ApiError::UnprocessableEntity {
+                ..
+            } => StatusCode::UNPROCESSABLE_ENTITY,
+            | ApiError::Validation(_) => StatusCode::UNPROCESSABLE_ENTITY,
+            | ApiError::PayloadTooLarge(_) => StatusCode::PAYLOAD_TOO_LARGE,
+            | ApiError::UnsupportedMediaType(_) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            | ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        if let ApiError::Internal(message) = self {
+            capture_message(message, current_request_id().as_deref());
+        }
+
+        let mut res = HttpResponse::build(self.status_code());
+        if let ApiError::MethodNotAllowed {
+            allowed, ..
+        } = self
+        {
+            let allowed: Vec<&str> = allowed.iter().map(Method::as_str).collect();
+            res.insert_header((header::ALLOW, allowed.join(", ")));
+        }
+        if let ApiError::Unauthorized(_) = self {
+            res.insert_header((header::WWW_AUTHENTICATE, "Bearer"));
+        }
+
+        res.json(ErrorBody {
+            status: self.status_code().as_u16(),
+            error: self.code(),
+            message: self.message(),
+            constraint: self.constraint(),
+            field: self.field(),
+            fields: self.fields(),
+        })
+    }
+}
+
+/// Map database errors to their HTTP meaning.
+///
+/// Unique violations become `409`, foreign key violations `422` and missing rows `404`.
+/// Anything else is logged and reported as an opaque `500` so driver details don't leak.
+impl From<sqlx::Error> for ApiError {
+    fn from(e: sqlx::Error) -> Self {
+        if let sqlx::Error::RowNotFound = e {
+            return ApiError::NotFound("Resource not found".to_string());
+        }
+
+        if let sqlx::Error::Database(db_err) = &e {
+            let constraint = db_err.constraint().map(str::to_string);
+            match db_err.kind() {
+                | ErrorKind::UniqueViolation => {
+                    return ApiError::Conflict {
+                        message: "Resource already exists".to_string(),
+                        constraint,
+                    };
+                }
+                | ErrorKind::ForeignKeyViolation => {
+                    return ApiError::UnprocessableEntity {
+                        message: "Referenced resource does not exist".to_string(),
+                        constraint,
+                    };
+                }
+                | _ => {}
+            }
+        }
+
+        tracing::error!(error = %e, "Database error");
+        ApiError::Internal("Internal server error".to_string())
+    }
+}
+
+impl From<TransactionError> for ApiError {
+    fn from(e: TransactionError) -> Self {
+        match e {
+            | TransactionError::Database(e) => e.into(),
+            | TransactionError::Nested => {
+                tracing::error!(error = %e, "Transaction misuse");
+                ApiError::Internal("Internal server error".to_string())
+            }
+        }
+    }
+}
+
+/// Collect the violations of every field, keyed by dotted path such as `items[0].name`.
+impl From<ValidationErrors> for ApiError {
+    fn from(errors: ValidationErrors) -> Self {
+        let mut fields = BTreeMap::new();
+        collect_violations(&errors, "", &mut fields);
+        ApiError::Validation(fields)
+    }
+}
+
+fn collect_violations(
+    errors: &ValidationErrors,
+    prefix: &str,
+    fields: &mut BTreeMap<String, Vec<String>>,
+) {
+    for (field, kind) in errors.errors() {
+        let path = match prefix.is_empty() {
+            | true => field.to_string(),
+            | false => format!("{prefix}.{field}"),
+        };
+
+        match kind {
+            | ValidationErrorsKind::Field(violations) => {
+                let messages = violations
+                    .iter()
+                    .map(|v| v.message.as_deref().unwrap_or(&v.code).to_string());
+                fields.entry(path).or_default().extend(messages);
+            }
+            | ValidationErrorsKind::Struct(errors) => collect_violations(errors, &path, fields),
+            | ValidationErrorsKind::List(items) => {
+                for (index, errors) in items {
+                    collect_violations(errors, &format!("{path}[{index}]"), fields);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::testing::database_error;
+
+    async fn body_of(error: ApiError) -> serde_json::Value {
+        let body = actix_web::body::to_bytes(error.error_response().into_body())
+            .await
+            .unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[actix_rt::test]
+    async fn test_sqlx_error_mapping() {
+        let cases = [
+            (sqlx::Error::RowNotFound, StatusCode::NOT_FOUND),
+            (database_error(1062, None), StatusCode::CONFLICT),
+            (database_error(1452, None), StatusCode::UNPROCESSABLE_ENTITY),
+            (database_error(3819, None), StatusCode::INTERNAL_SERVER_ERROR),
+            (sqlx::Error::PoolTimedOut, StatusCode::INTERNAL_SERVER_ERROR),
+        ];
+
+        for (error, status) in cases {
+            assert_eq!(ApiError::from(error).status_code(), status);
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_constraint_is_included_when_available() {
+        let error = ApiError::from(database_error(1062, Some("users_email_key")));
+        let body = body_of(error).await;
+        assert_eq!(body["status"], 409);
+        assert_eq!(body["error"], "conflict");
+        assert_eq!(body["constraint"], "users_email_key");
+
+        let body = body_of(ApiError::from(sqlx::Error::RowNotFound)).await;
+        assert_eq!(body["error"], "not_found");
+        assert!(body.get("constraint").is_none());
+    }
+
+    #[actix_rt::test]
+    async fn test_internal_error_hides_details() {
+        let body = body_of(ApiError::from(sqlx::Error::PoolTimedOut)).await;
+        assert_eq!(body["message"], "Internal server error");
+    }
+
+    #[test]
+    fn test_validation_errors_are_keyed_by_path() {
+        let mut item = ValidationErrors::new();
+        item.add("name", validator::ValidationError::new("required"));
+        let mut address = ValidationErrors::new();
+        address.add("city", validator::ValidationError::new("length"));
+
+        let mut errors = ValidationErrors::new();
+        errors.add("email", validator::ValidationError::new("email"));
+        errors.merge_self("address", Err(address));
+        errors
+            .errors_mut()
+            .insert("items".into(), ValidationErrorsKind::List([(0, Box::new(item))].into()));
+
+        let ApiError::Validation(fields) = ApiError::from(errors) else {
+            panic!("expected a validation error");
+        };
+        assert_eq!(
+            fields,
+            BTreeMap::from([
+                ("address.city".to_string(), vec!["length".to_string()]),
+                ("email".to_string(), vec!["email".to_string()]),
+                ("items[0].name".to_string(), vec!["required".to_string()]),
+            ])
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_error_body() {
+        let resp = ApiError::UnsupportedMediaType("nope".to_string()).error_response();
+        assert_eq!(resp.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let body = actix_web::body::to_bytes(resp.into_body()).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(
+            body,
+            serde_json::json!({
+                "status": 415,
+                "error": "unsupported_media_type",
+                "message": "nope",
+            })
+        );
+    }
+}
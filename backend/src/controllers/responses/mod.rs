@@ -1 +1,206 @@
+use actix_web::{
+    HttpRequest,
+    HttpResponse,
+    Responder,
+    ResponseError,
+    body::BoxBody,
+    http::header::{self, Accept, Header},
+};
+pub use error::ApiError;
+use serde::Serialize;
 
+mod error;
+mod table;
+
+const MSGPACK_CONTENT_TYPES: &[&str] = &["application/x-msgpack", "application/msgpack"];
+
+const YAML_CONTENT_TYPES: &[&str] = &["application/yaml", "application/x-yaml", "text/yaml"];
+
+const CSV_CONTENT_TYPE: &str = "text/csv";
+
+/// Responder that serializes its value as JSON, MessagePack, YAML or CSV depending on `Accept`.
+///
+/// The highest ranked supported media type wins. JSON is used when the header is missing or
+/// unparsable, and a `406` [`ApiError::NotAcceptable`] is returned when it lists no supported
+/// type. See [`table::to_csv`] for how values are laid out as CSV.
+pub struct Negotiated<T>(pub T);
+
+#[derive(Debug, PartialEq)]
+enum Format {
+    Json,
+    MsgPack,
+    Yaml,
+    Csv,
+}
+
+impl Format {
+    /// The format to respond with, `None` if `Accept` lists no supported type.
+    fn from_request(req: &HttpRequest) -> Option<Self> {
+        let accept = match Accept::parse(req) {
+            | Ok(accept) if !accept.is_empty() => accept,
+            | _ => return Some(Format::Json),
+        };
+
+        accept
+            .ranked()
+            .iter()
+            .find_map(|mime| match mime.essence_str() {
+                | "application/json" | "*/*" | "application/*" => Some(Format::Json),
+                | essence if MSGPACK_CONTENT_TYPES.contains(&essence) => Some(Format::MsgPack),
+                | essence if YAML_CONTENT_TYPES.contains(&essence) => Some(Format::Yaml),
+                | CSV_CONTENT_TYPE | "text/*" => Some(Format::Csv),
+                | _ => None,
+            })
+    }
+}
+
+impl<T: Serialize> Responder for Negotiated<T> {
+    type Body = BoxBody;
+
+    fn respond_to(self, req: &HttpRequest) -> HttpResponse<Self::Body> {
+        let Some(format) = Format::from_request(req) else {
+            return ApiError::NotAcceptable(format!(
+                "Supported response types: application/json, {}, {}, {CSV_CONTENT_TYPE}",
+                MSGPACK_CONTENT_TYPES[0], YAML_CONTENT_TYPES[0]
+            ))
+            .error_response();
+        };
+
+        let mut res = HttpResponse::Ok();
+        res.insert_header((header::VARY, "Accept"));
+
+        let body = match format {
+            | Format::Json => return res.json(&self.0),
+            | Format::MsgPack => rmp_serde::to_vec_named(&self.0)
+                .map(|body| (MSGPACK_CONTENT_TYPES[0], body))
+                .map_err(|e| e.to_string()),
+            | Format::Yaml => serde_yaml::to_string(&self.0)
+                .map(|body| (YAML_CONTENT_TYPES[0], body.into_bytes()))
+                .map_err(|e| e.to_string()),
+            | Format::Csv => serde_json::to_value(&self.0)
+                .map_err(|e| e.to_string())
+                .and_then(|value| table::to_csv(&value).map_err(|e| e.to_string()))
+                .map(|body| (CSV_CONTENT_TYPE, body)),
+        };
+
+        match body {
+            | Ok((content_type, body)) => res.content_type(content_type).body(body),
+            | Err(e) => {
+                tracing::error!(error = %e, format = ?format, "Failed to serialize response");
+                HttpResponse::InternalServerError().finish()
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{App, web};
+    use serde::Deserialize;
+
+    use super::*;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Example {
+        id: u32,
+        name: String,
+    }
+
+    fn example() -> Example {
+        Example {
+            id: 7,
+            name: "Example DTO".to_string(),
+        }
+    }
+
+    async fn handler() -> Negotiated<Example> {
+        Negotiated(example())
+    }
+
+    async fn call(accept: Option<&str>) -> actix_web::dev::ServiceResponse {
+        let app =
+            actix_web::test::init_service(App::new().route("/", web::get().to(handler))).await;
+        let mut req = actix_web::test::TestRequest::get().uri("/");
+        if let Some(accept) = accept {
+            req = req.insert_header((header::ACCEPT, accept));
+        }
+        actix_web::test::call_service(&app, req.to_request()).await
+    }
+
+    fn content_type(resp: &actix_web::dev::ServiceResponse) -> &str {
+        resp.headers()
+            .get(header::CONTENT_TYPE)
+            .unwrap()
+            .to_str()
+            .unwrap()
+    }
+
+    #[actix_rt::test]
+    async fn test_defaults_to_json() {
+        let resp = call(None).await;
+        assert_eq!(content_type(&resp), "application/json");
+
+        let body: Example = actix_web::test::read_body_json(resp).await;
+        assert_eq!(body, example());
+    }
+
+    #[actix_rt::test]
+    async fn test_json_when_requested() {
+        let resp = call(Some("application/json")).await;
+        assert_eq!(content_type(&resp), "application/json");
+    }
+
+    #[actix_rt::test]
+    async fn test_msgpack_when_requested() {
+        let resp = call(Some("application/x-msgpack")).await;
+        assert_eq!(content_type(&resp), "application/x-msgpack");
+
+        let body = actix_web::test::read_body(resp).await;
+        let decoded: Example = rmp_serde::from_slice(&body).unwrap();
+        assert_eq!(decoded, example());
+    }
+
+    #[actix_rt::test]
+    async fn test_respects_quality_ranking() {
+        let resp = call(Some("application/json;q=0.5, application/msgpack")).await;
+        assert_eq!(content_type(&resp), "application/x-msgpack");
+    }
+
+    #[actix_rt::test]
+    async fn test_yaml_when_requested() {
+        let resp = call(Some("application/yaml")).await;
+        assert_eq!(content_type(&resp), "application/yaml");
+
+        let body = actix_web::test::read_body(resp).await;
+        let decoded: Example = serde_yaml::from_slice(&body).unwrap();
+        assert_eq!(decoded, example());
+    }
+
+    #[actix_rt::test]
+    async fn test_csv_when_requested() {
+        let resp = call(Some("text/csv")).await;
+        assert_eq!(content_type(&resp), "text/csv");
+
+        let body = actix_web::test::read_body(resp).await;
+        let decoded: Vec<Example> = csv::Reader::from_reader(&body[..])
+            .deserialize()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(decoded, [example()]);
+    }
+
+    #[actix_rt::test]
+    async fn test_unparsable_accept_falls_back_to_json() {
+        let resp = call(Some("not a media type")).await;
+        assert_eq!(content_type(&resp), "application/json");
+    }
+
+    #[actix_rt::test]
+    async fn test_unsupported_accept_is_not_acceptable() {
+        let resp = call(Some("text/html, image/png")).await;
+        assert_eq!(resp.status(), actix_web::http::StatusCode::NOT_ACCEPTABLE);
+
+        let body: serde_json::Value = actix_web::test::read_body_json(resp).await;
+        assert_eq!(body["error"], "not_acceptable");
+    }
+}
@@ -0,0 +1,107 @@
+use serde_json::Value;
+
+/// Column holding rows that are not JSON objects.
+const SCALAR_COLUMN: &str = "value";
+
+/// Render a serialized response as CSV.
+///
+/// A top-level array becomes one row per element, anything else a single row. Nested objects and
+/// arrays are flattened into dotted columns, so `{ "owner": { "email": "a@b.c" }, "tags": ["x"] }`
+/// yields the columns `owner.email` and `tags.0`. Rows that aren't objects go in a `value`
+/// column. `null` becomes an empty cell, while empty objects and arrays add no column. Columns
+/// are ordered by first appearance and rows lacking a column leave it empty.
+pub fn to_csv(value: &Value) -> Result<Vec<u8>, csv::Error> {
+    let rows: Vec<Vec<(String, String)>> = match value {
+        | Value::Array(items) => items.iter().map(flatten_row).collect(),
+        | value => vec![flatten_row(value)],
+    };
+
+    let mut columns: Vec<&str> = Vec::new();
+    for (column, _) in rows.iter().flatten() {
+        if !columns.contains(&column.as_str()) {
+            columns.push(column);
+        }
+    }
+
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    writer.write_record(&columns)?;
+    for row in &rows {
+        writer.write_record(columns.iter().map(|column| {
+            row.iter()
+                .find(|(name, _)| name == column)
+                .map_or("", |(_, cell)| cell.as_str())
+        }))?;
+    }
+
+    writer
+        .into_inner()
+        .map_err(|e| csv::Error::from(e.into_error()))
+}
+
+fn flatten_row(value: &Value) -> Vec<(String, String)> {
+    let mut cells = Vec::new();
+    match value {
+        | Value::Object(_) => flatten(value, String::new(), &mut cells),
+        | value => flatten(value, SCALAR_COLUMN.to_string(), &mut cells),
+    }
+    cells
+}
+
+fn flatten(value: &Value, path: String, cells: &mut Vec<(String, String)>) {
+    let child = |key: &str| match path.is_empty() {
+        | true => key.to_string(),
+        | false => format!("{path}.{key}"),
+    };
+
+    match value {
+        | Value::Object(map) if !map.is_empty() => {
+            for (key, value) in map {
+                flatten(value, child(key), cells);
+            }
+        }
+        | Value::Array(items) if !items.is_empty() => {
+            for (i, value) in items.iter().enumerate() {
+                flatten(value, child(&i.to_string()), cells);
+            }
+        }
+        | Value::Object(_) | Value::Array(_) => {}
+        | Value::Null => cells.push((path, String::new())),
+        | Value::String(s) => cells.push((path, s.clone())),
+        | scalar => cells.push((path, scalar.to_string())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn csv(value: Value) -> String {
+        String::from_utf8(to_csv(&value).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_nested_values_become_dotted_columns() {
+        let value = json!([
+            { "id": 1, "owner": { "email": "ada@example.com" }, "tags": ["welcome", "en"] },
+            { "id": 2, "owner": { "email": "grace@example.com", "name": "Grace" }, "tags": [] },
+        ]);
+
+        assert_eq!(
+            csv(value),
+            "id,owner.email,tags.0,tags.1,owner.name\n\
+             1,ada@example.com,welcome,en,\n\
+             2,grace@example.com,,,Grace\n"
+        );
+    }
+
+    #[test]
+    fn test_scalars_and_single_objects() {
+        assert_eq!(
+            csv(json!({ "name": "a, \"quoted\" name", "n": null })),
+            "n,name\n,\"a, \"\"quoted\"\" name\"\n"
+        );
+        assert_eq!(csv(json!([1, "two"])), "value\n1\ntwo\n");
+    }
+}
@@ -0,0 +1,126 @@
+use std::{future::Future, time::Duration};
+
+use futures_util::future::LocalBoxFuture;
+
+/// Cleanup step registered by a subsystem.
+struct Hook {
+    name: &'static str,
+    timeout: Duration,
+    run: Box<dyn FnOnce() -> LocalBoxFuture<'static, ()>>,
+}
+
+/// Ordered shutdown of the service's subsystems.
+///
+/// Subsystems register a hook once they are started. On shutdown the hooks run one at a time in
+/// reverse registration order, so the HTTP server stops taking requests before the database
+/// pools it uses are closed. A hook overrunning its timeout is abandoned and shutdown moves on
+/// to the next one.
+#[derive(Default)]
+pub struct Shutdown {
+    hooks: Vec<Hook>,
+}
+
+impl Shutdown {
+    pub fn register<F, Fut>(&mut self, name: &'static str, timeout: Duration, hook: F)
+    where
+        F: FnOnce() -> Fut + 'static,
+        Fut: Future<Output = ()> + 'static,
+    {
+        self.hooks.push(Hook {
+            name,
+            timeout,
+            run: Box::new(move || Box::pin(hook())),
+        });
+    }
+
+    /// Run the hooks, last registered first, returning the names of those that timed out.
+    pub async fn run(self) -> Vec<&'static str> {
+        let mut timed_out = Vec::new();
+        for hook in self.hooks.into_iter().rev() {
+            tracing::info!(hook = hook.name, "Running shutdown hook");
+            match actix_rt::time::timeout(hook.timeout, (hook.run)()).await {
+                | Ok(()) => tracing::info!(hook = hook.name, "Shutdown hook completed"),
+                | Err(_) => {
+                    tracing::warn!(
+                        hook = hook.name,
+                        timeout_ms = hook.timeout.as_millis() as u64,
+                        "Shutdown hook timed out"
+                    );
+                    timed_out.push(hook.name);
+                }
+            }
+        }
+
+        tracing::info!("Shutdown complete");
+        timed_out
+    }
+}
+
+/// Resolve once the process receives `SIGINT` or, on Unix, `SIGTERM`.
+pub async fn signal() -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use actix_rt::signal::unix::{SignalKind, signal};
+
+        let mut terminate = signal(SignalKind::terminate())?;
+        tokio::select! {
+            result = actix_rt::signal::ctrl_c() => result,
+            _ = terminate.recv() => Ok(()),
+        }
+    }
+
+    #[cfg(not(unix))]
+    actix_rt::signal::ctrl_c().await
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::*;
+
+    fn recording_hook(
+        events: &Rc<RefCell<Vec<&'static str>>>,
+        name: &'static str,
+    ) -> impl FnOnce() -> LocalBoxFuture<'static, ()> + 'static {
+        let events = events.clone();
+        move || {
+            Box::pin(async move {
+                events.borrow_mut().push(name);
+            })
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_hooks_run_in_reverse_order() {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let mut shutdown = Shutdown::default();
+        for name in ["database", "scheduler", "http"] {
+            shutdown.register(name, Duration::from_secs(1), recording_hook(&events, name));
+        }
+
+        let timed_out = shutdown.run().await;
+
+        assert!(timed_out.is_empty());
+        assert_eq!(*events.borrow(), ["http", "scheduler", "database"]);
+    }
+
+    #[actix_rt::test]
+    async fn test_slow_hook_is_abandoned() {
+        let events = Rc::new(RefCell::new(Vec::new()));
+        let mut shutdown = Shutdown::default();
+        shutdown.register("database", Duration::from_secs(1), recording_hook(&events, "database"));
+        let slow_events = events.clone();
+        shutdown.register("http", Duration::from_millis(20), move || async move {
+            actix_rt::time::sleep(Duration::from_secs(5)).await;
+            slow_events.borrow_mut().push("http");
+        });
+
+        let started = std::time::Instant::now();
+        let timed_out = shutdown.run().await;
+
+        assert_eq!(timed_out, ["http"]);
+        assert_eq!(*events.borrow(), ["database"]);
+        assert!(started.elapsed() < Duration::from_secs(1));
+    }
+}
@@ -1,22 +1,42 @@
+use std::time::Duration;
+
 use actix_cors::Cors;
-use actix_web::{App, HttpServer, web};
-use config::LoggingConfig;
-use config::register_configs;
-use controllers::base::{health_check, not_found};
-use utils::logging::init_logging;
+use actix_web::{App, HttpServer, middleware::Condition, web};
+use config::{AppConfig, DatabaseConfig, LoggingConfig, SeederConfig, register_configs};
+use controllers::{base::not_found, requests::json_config};
+use lifecycle::Shutdown;
+use middleware::{AccessLog, BodyLogger, SlowRequestLogger, security_headers};
+use sqlx::{Connection, MySqlConnection};
+use startup::Dependency;
+use state::AppState;
+use utils::{
+    build_info::BUILD_INFO,
+    database::{connect_with_retry, replica_pool},
+    errors::{init_error_reporting, spawn_reported},
+    logging::init_logging,
+    routes::RouteTable,
+};
 use zirv_config::read_config;
 use zirv_db_sqlx::{get_db_pool, init_db_pool};
 
 mod config;
 mod controllers;
+mod lifecycle;
+mod middleware;
 mod models;
 mod router;
 mod seeder;
+mod startup;
+mod state;
 mod utils;
 
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
-    register_configs();
+    if let Err(e) = register_configs() {
+        // Logging isn't initialized yet
+        eprintln!("Failed to load configuration: {e}");
+        std::process::exit(1);
+    }
 
     // Initialize structured logging for Kibana
     let logging_config = read_config!("logging", LoggingConfig).unwrap();
@@ -25,36 +45,127 @@ async fn main() -> std::io::Result<()> {
         &logging_config.environment,
         &logging_config.level,
         &logging_config.format,
+        &logging_config.schema,
     )
     .expect("Failed to initialize logging");
 
+    tracing::info!(
+        version = BUILD_INFO.version,
+        git_sha = BUILD_INFO.git_sha,
+        build_timestamp = BUILD_INFO.build_timestamp,
+        rustc_version = BUILD_INFO.rustc_version,
+        "Starting service"
+    );
+
+    // Report panics through tracing (and Sentry when enabled)
+    let _error_reporting = init_error_reporting(&logging_config);
+
+    let database_url = read_config!("database.url", String).unwrap();
+
+    // In development the database container is often still starting, wait for it
+    let app_config = read_config!("app", AppConfig).unwrap();
+    if app_config.wait_for_dependencies {
+        let database = Dependency::new(
+            "database",
+            Duration::from_secs(app_config.dependency_timeout_secs),
+            || async { MySqlConnection::connect(&database_url).await?.close().await },
+        );
+        if let Err(e) = startup::wait_for(vec![database], Duration::from_secs(1)).await {
+            tracing::error!(error = %e, "Dependencies are unavailable");
+            std::process::exit(1);
+        }
+    }
+
+    // Make sure the database is reachable before building the pool, which panics otherwise
+    let connect_retries = read_config!("database.connect_retries", u32).unwrap();
+    let connected = connect_with_retry(connect_retries, Duration::from_millis(500), || async {
+        MySqlConnection::connect(&database_url).await?.close().await
+    })
+    .await;
+    if let Err(e) = connected {
+        tracing::error!(
+            error = %e,
+            attempts = connect_retries + 1,
+            "Could not connect to the database, check DATABASE_URL"
+        );
+        std::process::exit(1);
+    }
+
     init_db_pool!();
 
-    let pool = get_db_pool!();
-
-    // Migrate the database
-    tracing::info!("Running database migrations");
-    sqlx::migrate!("../migrations")
-        .run(pool)
-        .await
-        .expect("Failed to run migrations");
-    tracing::info!("Database migrations completed");
-
-    // Seed the database
-    tracing::info!("Seeding database");
-    match seeder::seed_database().await {
-        | Ok(_) => tracing::info!("Database seeded successfully"),
-        | Err(e) => tracing::error!(error = ?e, "Failed to seed database"),
+    let read_db = match replica_pool(&read_config!("database", DatabaseConfig).unwrap()) {
+        | Ok(pool) => pool,
+        | Err(e) => {
+            tracing::error!(
+                error = %e,
+                "Invalid read replica configuration, check DATABASE_REPLICA_URL"
+            );
+            std::process::exit(1);
+        }
     };
 
-    let host = read_config!("app.host", String).unwrap();
-    let port = read_config!("app.port", u16).unwrap();
+    let state = web::Data::new(
+        AppState::new(get_db_pool!().clone(), app_config, logging_config).with_read_pool(read_db),
+    );
+
+    // Migrate and seed in the background; /health/ready reports ready once this completes
+    let startup_state = state.clone();
+    let startup_deadline = Duration::from_secs(state.app.startup_deadline_secs);
+    spawn_reported("startup", async move {
+        let steps = async {
+            tracing::info!("Running database migrations");
+            sqlx::migrate!("../migrations")
+                .run(&startup_state.db)
+                .await?;
+            tracing::info!("Database migrations completed");
+
+            tracing::info!("Seeding database");
+            let seeder_config =
+                read_config!("seeder", SeederConfig).ok_or("seeder config is not registered")?;
+            let environment = &startup_state.app.environment;
+            let report =
+                seeder::seed_database(&startup_state.db, environment, &seeder_config).await?;
+            tracing::info!(
+                files = report.files.len(),
+                rows = report.total_rows(),
+                "Database seeded successfully"
+            );
+
+            Ok::<_, Box<dyn std::error::Error + Send + Sync>>(())
+        };
+
+        let result =
+            startup::complete_startup(&startup_state.readiness, startup_deadline, steps).await;
+        if let Err(e) = result {
+            tracing::error!(error = %e, "Service failed to start");
+            std::process::exit(1);
+        }
+    });
+
+    let slow_request_threshold = Duration::from_millis(state.logging.slow_request_ms);
+    let access_log_sample_rate = state.logging.access_log_sample_rate;
+    let log_bodies = state.logging.log_bodies;
+    let log_body_max_bytes = state.logging.log_body_max_bytes;
+    let log_redact_fields = state.logging.log_redact_fields();
+
+    let json_exempt_paths = state.app.json_content_type_exempt_paths();
+    let enable_admin = state.app.enable_admin;
+    let admin_token = state.app.admin_token.clone();
+    if enable_admin && admin_token.is_empty() {
+        tracing::warn!("ADMIN_TOKEN is not set, the admin endpoints will reject every request");
+    }
+    let route_suggestions = state.app.route_suggestions;
+    let host = state.app.host.clone();
+    let port = state.app.port;
+    let shutdown_timeout = Duration::from_secs(state.app.shutdown_timeout_secs);
+    let db = state.db.clone();
+    let read_db = state.read_db.clone();
 
     // Start Actix Web Server
     let addr = format!("{}:{}", host, port);
     tracing::info!(address = %addr, "Starting HTTP server");
 
-    HttpServer::new(move || {
+    let server = HttpServer::new(move || {
         // Configure CORS to allow only localhost
         let cors = Cors::default()
             .allowed_origin("http://localhost")
@@ -71,14 +182,50 @@ async fn main() -> std::io::Result<()> {
             .supports_credentials()
             .max_age(3600);
 
+        let mut routes = Vec::new();
         App::new()
+            .configure(|cfg| {
+                routes = router::mount(cfg, enable_admin, &admin_token, &json_exempt_paths)
+            })
+            .app_data(web::Data::new(RouteTable::new(routes, route_suggestions)))
+            .app_data(state.clone())
+            .app_data(json_config())
+            .wrap(SlowRequestLogger::new(slow_request_threshold))
+            .wrap(AccessLog::new(access_log_sample_rate))
+            .wrap(Condition::new(
+                log_bodies,
+                BodyLogger::new(log_body_max_bytes, log_redact_fields.clone()),
+            ))
             .wrap(tracing_actix_web::TracingLogger::default())
             .wrap(cors)
-            .service(health_check)
-            .service(router::get())
+            .wrap(security_headers(&state.app))
             .default_service(web::route().to(not_found))
     })
     .bind((host, port))?
-    .run()
-    .await
+    .shutdown_timeout(shutdown_timeout.as_secs())
+    .disable_signals()
+    .run();
+
+    // Hooks run last registered first: stop taking requests, then close the pools
+    let mut shutdown = Shutdown::default();
+    shutdown.register("database", shutdown_timeout, move || async move {
+        db.close().await;
+        if let Some(read_db) = read_db {
+            read_db.close().await;
+        }
+    });
+    let server_handle = server.handle();
+    shutdown.register("http", shutdown_timeout, move || async move {
+        server_handle.stop(true).await;
+    });
+
+    let mut server = actix_rt::spawn(server);
+    tokio::select! {
+        result = &mut server => return result.map_err(std::io::Error::other)?,
+        result = lifecycle::signal() => result?,
+    }
+
+    tracing::info!("Shutdown signal received");
+    shutdown.run().await;
+    server.await.map_err(std::io::Error::other)?
 }
@@ -0,0 +1,176 @@
+use std::{
+    cell::RefCell,
+    future::{Future, Ready, ready},
+    pin::Pin,
+    rc::Rc,
+    time::Instant,
+};
+
+use actix_web::{
+    Error,
+    dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready},
+    http::StatusCode,
+};
+use rand::{Rng, SeedableRng, rngs::StdRng};
+
+/// Middleware emitting one `INFO` access log event per request.
+///
+/// This is the only line logged for a request that logs nothing else: the request span opened by
+/// `TracingLogger` isn't logged itself. Successful (2xx) requests are only logged for a
+/// `sample_rate` fraction of requests to keep logging cheap under heavy load. Every other status
+/// is always logged.
+#[derive(Clone, Copy, Debug)]
+pub struct AccessLog {
+    sample_rate: f64,
+}
+
+impl AccessLog {
+    pub fn new(sample_rate: f64) -> Self {
+        Self {
+            sample_rate: sample_rate.clamp(0.0, 1.0),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for AccessLog
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = AccessLogMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(AccessLogMiddleware {
+            service,
+            sample_rate: self.sample_rate,
+            rng: Rc::new(RefCell::new(StdRng::from_entropy())),
+        }))
+    }
+}
+
+pub struct AccessLogMiddleware<S> {
+    service: S,
+    sample_rate: f64,
+    rng: Rc<RefCell<StdRng>>,
+}
+
+impl<S, B> Service<ServiceRequest> for AccessLogMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let sample_rate = self.sample_rate;
+        let rng = self.rng.clone();
+        let started = Instant::now();
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let res = fut.await?;
+
+            if should_log(res.status(), sample_rate, &mut *rng.borrow_mut()) {
+                let request = res.request();
+                let route = request
+                    .match_pattern()
+                    .unwrap_or_else(|| request.path().to_string());
+
+                tracing::info!(
+                    http.method = %request.method(),
+                    http.route = %route,
+                    http.status_code = res.status().as_u16(),
+                    http.response_time_ms = started.elapsed().as_millis() as u64,
+                    "finished processing request"
+                );
+            }
+
+            Ok(res)
+        })
+    }
+}
+
+/// Whether a request answered with `status` gets an access log event.
+fn should_log(status: StatusCode, sample_rate: f64, rng: &mut impl Rng) -> bool {
+    if !status.is_success() {
+        return true;
+    }
+
+    sample_rate >= 1.0 || rng.gen_bool(sample_rate.clamp(0.0, 1.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{App, HttpResponse, web};
+
+    use super::*;
+    use crate::utils::testing::CapturedLogs;
+
+    fn sampled(status: StatusCode, sample_rate: f64, seed: u64) -> Vec<bool> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        (0..1000)
+            .map(|_| should_log(status, sample_rate, &mut rng))
+            .collect()
+    }
+
+    #[test]
+    fn test_sampling_is_deterministic_with_seed() {
+        let first = sampled(StatusCode::OK, 0.1, 42);
+        assert_eq!(first, sampled(StatusCode::OK, 0.1, 42));
+
+        let logged = first.iter().filter(|logged| **logged).count();
+        assert!((50..150).contains(&logged), "logged {logged} of 1000");
+    }
+
+    #[test]
+    fn test_sample_rate_bounds() {
+        assert!(sampled(StatusCode::OK, 1.0, 7).iter().all(|logged| *logged));
+        assert!(
+            sampled(StatusCode::OK, 0.0, 7)
+                .iter()
+                .all(|logged| !*logged)
+        );
+    }
+
+    #[test]
+    fn test_errors_are_never_sampled_out() {
+        for status in [
+            StatusCode::NOT_FOUND,
+            StatusCode::INTERNAL_SERVER_ERROR,
+            StatusCode::PERMANENT_REDIRECT,
+        ] {
+            assert!(sampled(status, 0.0, 7).iter().all(|logged| *logged));
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_middleware_logs_errors_only_at_zero_rate() {
+        let logs = CapturedLogs::default();
+        let _guard = logs.set_default();
+
+        let app = actix_web::test::init_service(
+            App::new()
+                .wrap(AccessLog::new(0.0))
+                .route("/ok", web::get().to(HttpResponse::Ok))
+                .route("/fail", web::get().to(HttpResponse::InternalServerError)),
+        )
+        .await;
+        for uri in ["/ok", "/fail"] {
+            let req = actix_web::test::TestRequest::get().uri(uri).to_request();
+            actix_web::test::call_service(&app, req).await;
+        }
+
+        let output = logs.contents();
+        assert!(output.contains("http.route=/fail"));
+        assert!(output.contains("http.status_code=500"));
+        assert!(!output.contains("http.route=/ok"));
+    }
+}
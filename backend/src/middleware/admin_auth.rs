@@ -0,0 +1,161 @@
+use std::{
+    future::{Future, Ready, ready},
+    pin::Pin,
+    rc::Rc,
+};
+
+use actix_web::{
+    Error,
+    body::EitherBody,
+    dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready},
+    http::header,
+};
+
+use crate::controllers::responses::ApiError;
+
+/// Middleware rejecting requests without `Authorization: Bearer <token>` with `401 Unauthorized`.
+///
+/// Guards the operator endpoints. The token is compared in constant time, and an empty token
+/// rejects every request so enabling the endpoints without configuring one doesn't expose them.
+#[derive(Clone, Debug)]
+pub struct RequireAdminToken {
+    token: Rc<String>,
+}
+
+impl RequireAdminToken {
+    pub fn new(token: String) -> Self {
+        Self {
+            token: Rc::new(token),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequireAdminToken
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = RequireAdminTokenMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequireAdminTokenMiddleware {
+            service,
+            token: self.token.clone(),
+        }))
+    }
+}
+
+pub struct RequireAdminTokenMiddleware<S> {
+    service: S,
+    token: Rc<String>,
+}
+
+impl<S> RequireAdminTokenMiddleware<S> {
+    fn check(&self, req: &ServiceRequest) -> Result<(), ApiError> {
+        let presented = req
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("Bearer "));
+
+        match presented {
+            | Some(presented)
+                if !self.token.is_empty() && constant_time_eq(presented, &self.token) =>
+            {
+                Ok(())
+            }
+            | Some(_) => Err(ApiError::Unauthorized("Invalid admin token".to_string())),
+            | None => Err(ApiError::Unauthorized("Missing admin token".to_string())),
+        }
+    }
+}
+
+impl<S, B> Service<ServiceRequest> for RequireAdminTokenMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        match self.check(&req) {
+            | Ok(()) => {
+                let fut = self.service.call(req);
+                Box::pin(async move { Ok(fut.await?.map_into_left_body()) })
+            }
+            | Err(e) => Box::pin(ready(Ok(req.error_response(e).map_into_right_body()))),
+        }
+    }
+}
+
+/// Compare two strings in time depending only on their lengths, not on where they differ.
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{App, HttpResponse, http::StatusCode, web};
+
+    use super::*;
+
+    async fn get(token: &str, authorization: Option<&str>) -> ServiceResponse {
+        let app = actix_web::test::init_service(
+            App::new().service(
+                web::scope("/admin")
+                    .wrap(RequireAdminToken::new(token.to_string()))
+                    .route("/config", web::get().to(HttpResponse::Ok)),
+            ),
+        )
+        .await;
+
+        let mut req = actix_web::test::TestRequest::get().uri("/admin/config");
+        if let Some(authorization) = authorization {
+            req = req.insert_header((header::AUTHORIZATION, authorization));
+        }
+        actix_web::test::call_service(&app, req.to_request()).await
+    }
+
+    #[actix_rt::test]
+    async fn test_missing_token_is_rejected() {
+        let resp = get("s3cret", None).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(resp.headers().get(header::WWW_AUTHENTICATE).unwrap(), "Bearer");
+
+        let body: serde_json::Value = actix_web::test::read_body_json(resp).await;
+        assert_eq!(body["error"], "unauthorized");
+        assert_eq!(body["message"], "Missing admin token");
+    }
+
+    #[actix_rt::test]
+    async fn test_wrong_token_is_rejected() {
+        for authorization in ["Bearer s3cre", "Bearer s3cret!", "Basic s3cret", "s3cret"] {
+            let resp = get("s3cret", Some(authorization)).await;
+            assert_eq!(resp.status(), StatusCode::UNAUTHORIZED, "{authorization}");
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_matching_token_passes_through() {
+        let resp = get("s3cret", Some("Bearer s3cret")).await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[actix_rt::test]
+    async fn test_empty_token_rejects_everything() {
+        let resp = get("", Some("Bearer ")).await;
+        assert_eq!(resp.status(), StatusCode::UNAUTHORIZED);
+    }
+}
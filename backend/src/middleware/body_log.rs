@@ -0,0 +1,563 @@
+use std::{
+    cell::RefCell,
+    future::{Future, Ready, ready},
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+use actix_web::{
+    Error,
+    HttpMessage,
+    body::{BodySize, BoxBody, MessageBody},
+    dev::{Payload, Service, ServiceRequest, ServiceResponse, Transform, forward_ready},
+    http::header::{self, HeaderMap},
+    web::{Bytes, BytesMut},
+};
+use futures_util::StreamExt;
+use serde_json::{Map, Value};
+use tracing::Span;
+
+/// Placeholder logged instead of secret header and body values.
+const REDACTED: &str = "****";
+
+/// Request headers included in the log event.
+const LOGGED_HEADERS: &[&str] =
+    &["accept", "authorization", "content-length", "content-type", "user-agent", "x-api-key"];
+
+/// Logged request headers whose value is replaced by [`REDACTED`].
+const REDACTED_HEADERS: &[&str] = &["authorization", "x-api-key"];
+
+/// Middleware logging request and response bodies for debugging client reports.
+///
+/// Meant to be switched on temporarily. Both bodies stream through unchanged while the first
+/// `max_bytes` of JSON bodies are kept and logged with the configured fields redacted; other
+/// bodies are only counted and summarized by content type and size. The event is logged once the
+/// response body has been sent or dropped.
+#[derive(Clone, Debug)]
+pub struct BodyLogger {
+    config: Rc<BodyLogConfig>,
+}
+
+#[derive(Debug)]
+struct BodyLogConfig {
+    max_bytes: usize,
+    redact_fields: Vec<String>,
+}
+
+impl BodyLogger {
+    pub fn new(max_bytes: usize, redact_fields: Vec<String>) -> Self {
+        Self {
+            config: Rc::new(BodyLogConfig {
+                max_bytes,
+                redact_fields: redact_fields
+                    .into_iter()
+                    .map(|field| field.to_ascii_lowercase())
+                    .collect(),
+            }),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for BodyLogger
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Transform = BodyLoggerMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(BodyLoggerMiddleware {
+            service: Rc::new(service),
+            config: self.config.clone(),
+        }))
+    }
+}
+
+pub struct BodyLoggerMiddleware<S> {
+    service: Rc<S>,
+    config: Rc<BodyLogConfig>,
+}
+
+impl<S, B> Service<ServiceRequest> for BodyLoggerMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: MessageBody + 'static,
+{
+    type Response = ServiceResponse<BoxBody>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, mut req: ServiceRequest) -> Self::Future {
+        let service = self.service.clone();
+        let config = self.config.clone();
+
+        Box::pin(async move {
+            let request_body =
+                Rc::new(RefCell::new(BodyCapture::new(req.content_type(), config.max_bytes)));
+            let capture = request_body.clone();
+            let payload = req.take_payload().inspect(move |chunk| {
+                if let Ok(chunk) = chunk {
+                    capture.borrow_mut().record(chunk);
+                }
+            });
+            req.set_payload(Payload::Stream {
+                payload: Box::pin(payload),
+            });
+
+            let method = req.method().to_string();
+            let path = req.path().to_string();
+            let headers = format_headers(req.headers());
+
+            let res = service.call(req).await?;
+
+            let content_type = res
+                .headers()
+                .get(header::CONTENT_TYPE)
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or_default();
+            let exchange = Exchange {
+                method,
+                path,
+                headers,
+                status: res.status().as_u16(),
+                request_body,
+                response_body: BodyCapture::new(content_type, config.max_bytes),
+                config,
+                span: Span::current(),
+            };
+
+            Ok(res
+                .map_body(|_, body| LoggedBody {
+                    body: body.boxed(),
+                    exchange,
+                })
+                .map_into_boxed_body())
+        })
+    }
+}
+
+/// Everything logged about one request, logged when dropped along with the response body.
+struct Exchange {
+    method: String,
+    path: String,
+    headers: String,
+    status: u16,
+    request_body: Rc<RefCell<BodyCapture>>,
+    response_body: BodyCapture,
+    config: Rc<BodyLogConfig>,
+    /// Request span the event belongs to; the body is sent outside of it.
+    span: Span,
+}
+
+impl Drop for Exchange {
+    fn drop(&mut self) {
+        let redact_fields = &self.config.redact_fields;
+        let request_body = self.request_body.borrow().format(redact_fields);
+        let response_body = self.response_body.format(redact_fields);
+
+        self.span.in_scope(|| {
+            tracing::info!(
+                http.method = %self.method,
+                http.path = %self.path,
+                http.request.headers = %self.headers,
+                http.request.body = %request_body,
+                http.status_code = self.status,
+                http.response.body = %response_body,
+                "HTTP exchange"
+            );
+        });
+    }
+}
+
+/// Response body passed through to the client while its start is kept for the log.
+struct LoggedBody {
+    body: BoxBody,
+    exchange: Exchange,
+}
+
+impl MessageBody for LoggedBody {
+    type Error = <BoxBody as MessageBody>::Error;
+
+    fn size(&self) -> BodySize {
+        self.body.size()
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let this = self.get_mut();
+        let poll = Pin::new(&mut this.body).poll_next(cx);
+        if let Poll::Ready(Some(Ok(chunk))) = &poll {
+            this.exchange.response_body.record(chunk);
+        }
+        poll
+    }
+}
+
+/// Size of a body and, for JSON, up to `max_bytes` of its start.
+struct BodyCapture {
+    content_type: String,
+    is_json: bool,
+    max_bytes: usize,
+    head: BytesMut,
+    size: usize,
+}
+
+impl BodyCapture {
+    fn new(content_type: &str, max_bytes: usize) -> Self {
+        let is_json = content_type
+            .split(';')
+            .next()
+            .map(str::trim)
+            .is_some_and(|essence| essence == "application/json" || essence.ends_with("+json"));
+
+        Self {
+            content_type: content_type.to_string(),
+            is_json,
+            max_bytes,
+            head: BytesMut::new(),
+            size: 0,
+        }
+    }
+
+    fn record(&mut self, chunk: &[u8]) {
+        self.size += chunk.len();
+        if self.is_json {
+            let room = self.max_bytes.saturating_sub(self.head.len());
+            self.head.extend_from_slice(&chunk[..room.min(chunk.len())]);
+        }
+    }
+
+    /// Render the body for logging: redacted and truncated JSON, or a summary for anything else.
+    fn format(&self, redact_fields: &[String]) -> String {
+        if self.size == 0 {
+            return String::new();
+        }
+
+        let summary = || {
+            let content_type = match self.content_type.is_empty() {
+                | true => "unknown content type",
+                | false => &self.content_type,
+            };
+            format!("<{content_type}, {} bytes>", self.size)
+        };
+        if !self.is_json {
+            return summary();
+        }
+
+        if self.head.len() == self.size {
+            let Ok(mut value) = serde_json::from_slice::<Value>(&self.head) else {
+                return summary();
+            };
+            redact_fields_in(&mut value, redact_fields);
+            return truncate(value.to_string(), self.max_bytes, self.size);
+        }
+
+        // Cut off mid-document, so redact the text itself
+        let text = match std::str::from_utf8(&self.head) {
+            | Ok(text) => text,
+            | Err(e) => std::str::from_utf8(&self.head[..e.valid_up_to()]).unwrap_or_default(),
+        };
+        truncate(redact_partial(text, redact_fields), self.max_bytes, self.size)
+    }
+}
+
+/// Render the logged request headers as a JSON object with secrets redacted.
+fn format_headers(headers: &HeaderMap) -> String {
+    let mut logged = Map::new();
+    for &name in LOGGED_HEADERS {
+        let Some(value) = headers.get(name) else {
+            continue;
+        };
+
+        let value = match REDACTED_HEADERS.contains(&name) {
+            | true => REDACTED.to_string(),
+            | false => value.to_str().unwrap_or("<binary>").to_string(),
+        };
+        logged.insert(name.to_string(), value.into());
+    }
+
+    Value::Object(logged).to_string()
+}
+
+/// Replace the values of `fields` (matched case-insensitively) anywhere in `value`.
+fn redact_fields_in(value: &mut Value, fields: &[String]) {
+    match value {
+        | Value::Object(map) => {
+            for (key, value) in map.iter_mut() {
+                match fields.contains(&key.to_ascii_lowercase()) {
+                    | true => *value = REDACTED.into(),
+                    | false => redact_fields_in(value, fields),
+                }
+            }
+        }
+        | Value::Array(values) => {
+            for value in values {
+                redact_fields_in(value, fields);
+            }
+        }
+        | _ => {}
+    }
+}
+
+/// Replace the values of `fields` in the start of a JSON document that was cut off, copying the
+/// rest of the text as is.
+fn redact_partial(json: &str, fields: &[String]) -> String {
+    let mut redacted = String::with_capacity(json.len());
+    let mut rest = json;
+    while let Some(start) = rest.find('"') {
+        redacted.push_str(&rest[..start]);
+        let (string, after) = split_string(&rest[start..]);
+        redacted.push_str(string);
+        rest = after;
+
+        let key = string.trim_matches('"').to_ascii_lowercase();
+        let value = rest.trim_start();
+        if let Some(value) = value.strip_prefix(':')
+            && fields.contains(&key)
+        {
+            redacted.push_str(&format!(":\"{REDACTED}\""));
+            rest = skip_value(value);
+        }
+    }
+    redacted.push_str(rest);
+    redacted
+}
+
+/// Split the JSON string starting `text` from what follows it; all of `text` if unterminated.
+fn split_string(text: &str) -> (&str, &str) {
+    let mut bytes = text.bytes().enumerate().skip(1);
+    while let Some((i, byte)) = bytes.next() {
+        match byte {
+            | b'\\' => {
+                bytes.next();
+            }
+            | b'"' => return text.split_at(i + 1),
+            | _ => {}
+        }
+    }
+    (text, "")
+}
+
+/// What follows the JSON value starting `text`, empty if the value is cut off.
+fn skip_value(text: &str) -> &str {
+    let text = text.trim_start();
+    if text.starts_with('"') {
+        return split_string(text).1;
+    }
+
+    let mut depth = 0usize;
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        match c {
+            | '"' => {
+                rest = split_string(rest).1;
+                continue;
+            }
+            | '{' | '[' => depth += 1,
+            | '}' | ']' if depth > 0 => {
+                depth -= 1;
+                if depth == 0 {
+                    return &rest[1..];
+                }
+            }
+            | ',' | '}' | ']' if depth == 0 => return rest,
+            | _ => {}
+        }
+        rest = &rest[c.len_utf8()..];
+    }
+    rest
+}
+
+/// Cut `text` to `max_bytes`, noting the `total` size of the body it was rendered from.
+fn truncate(mut text: String, max_bytes: usize, total: usize) -> String {
+    if text.len() <= max_bytes && total <= max_bytes {
+        return text;
+    }
+
+    let mut end = max_bytes.min(text.len());
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    text.truncate(end);
+    text.push_str(&format!("... ({total} bytes total)"));
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{App, HttpResponse, http::header::HeaderName, web};
+    use serde_json::json;
+
+    use super::*;
+    use crate::utils::testing::CapturedLogs;
+
+    /// Log `body` as it would be after streaming through in chunks of 7 bytes.
+    fn format_body(content_type: &str, body: &[u8], max_bytes: usize) -> String {
+        let mut capture = BodyCapture::new(content_type, max_bytes);
+        for chunk in body.chunks(7) {
+            capture.record(chunk);
+        }
+
+        let redact_fields = ["password", "token", "apikey"].map(String::from);
+        capture.format(&redact_fields)
+    }
+
+    #[test]
+    fn test_json_fields_are_redacted() {
+        let body = json!({
+            "user": "ada",
+            "password": "hunter2",
+            "credentials": [{ "apiKey": "abc", "Token": "def" }],
+        });
+
+        let logged =
+            format_body("application/json; charset=utf-8", body.to_string().as_bytes(), 1024);
+        let logged: Value = serde_json::from_str(&logged).unwrap();
+        assert_eq!(
+            logged,
+            json!({
+                "user": "ada",
+                "password": REDACTED,
+                "credentials": [{ "apiKey": REDACTED, "Token": REDACTED }],
+            })
+        );
+    }
+
+    #[test]
+    fn test_long_bodies_are_truncated() {
+        let body = json!({ "text": "é".repeat(100) }).to_string();
+
+        let logged = format_body("application/json", body.as_bytes(), 20);
+        assert!(logged.starts_with(r#"{"text":""#));
+        assert!(logged.ends_with(&format!("... ({} bytes total)", body.len())));
+        assert!(logged.len() < body.len());
+    }
+
+    #[test]
+    fn test_truncated_json_is_still_redacted() {
+        let body = json!({
+            "password": "hunter2",
+            "user": { "token": ["a", { "b": 1 }], "name": "ada" },
+            "notes": "x".repeat(100),
+        })
+        .to_string();
+
+        let cut = format_body("application/json", body.as_bytes(), body.len() - 1);
+        assert!(!cut.contains("hunter2"), "{cut}");
+        assert!(cut.contains(r#""name":"ada","token":"****"}"#), "{cut}");
+        assert!(cut.ends_with(&format!("... ({} bytes total)", body.len())), "{cut}");
+
+        assert_eq!(
+            redact_partial(r#"{"user":"ada","Password":"hun"#, &["password".to_string()]),
+            r#"{"user":"ada","Password":"****""#
+        );
+    }
+
+    #[test]
+    fn test_non_json_bodies_are_summarized() {
+        assert_eq!(
+            format_body("image/png", &[0x89, 0x50, 0x4e, 0x47], 1024),
+            "<image/png, 4 bytes>"
+        );
+        assert_eq!(
+            format_body("application/json", b"not json", 1024),
+            "<application/json, 8 bytes>"
+        );
+        assert_eq!(format_body("", b"", 1024), "");
+    }
+
+    #[test]
+    fn test_secret_headers_are_redacted() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::AUTHORIZATION, "Bearer secret".parse().unwrap());
+        headers.insert(HeaderName::from_static("x-api-key"), "secret".parse().unwrap());
+        headers.insert(header::CONTENT_TYPE, "application/json".parse().unwrap());
+        headers.insert(header::COOKIE, "session=secret".parse().unwrap());
+
+        let logged: Value = serde_json::from_str(&format_headers(&headers)).unwrap();
+        assert_eq!(
+            logged,
+            json!({
+                "authorization": REDACTED,
+                "x-api-key": REDACTED,
+                "content-type": "application/json",
+            })
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_bodies_are_logged_and_passed_through() {
+        let logs = CapturedLogs::default();
+        let _guard = logs.set_default();
+
+        let app = actix_web::test::init_service(
+            App::new()
+                .wrap(BodyLogger::new(1024, vec!["password".to_string()]))
+                .route(
+                    "/login",
+                    web::post().to(|body: web::Json<Value>| async move {
+                        HttpResponse::Ok().json(json!({ "user": body["user"], "token": "t0k3n" }))
+                    }),
+                ),
+        )
+        .await;
+        let req = actix_web::test::TestRequest::post()
+            .uri("/login")
+            .set_json(json!({ "user": "ada", "password": "hunter2" }))
+            .to_request();
+        let body: Value = actix_web::test::call_and_read_body_json(&app, req).await;
+        assert_eq!(body, json!({ "user": "ada", "token": "t0k3n" }));
+
+        let output = logs.contents();
+        assert!(output.contains("HTTP exchange"));
+        assert!(output.contains(r#"http.request.body={"password":"****","user":"ada"}"#));
+        assert!(output.contains(r#"http.response.body={"token":"t0k3n","user":"ada"}"#));
+        assert!(!output.contains("hunter2"));
+    }
+
+    #[actix_rt::test]
+    async fn test_large_bodies_stream_through() {
+        let logs = CapturedLogs::default();
+        let _guard = logs.set_default();
+
+        let app =
+            actix_web::test::init_service(App::new().wrap(BodyLogger::new(16, Vec::new())).route(
+                "/numbers",
+                web::post().to(|body: web::Bytes| async move {
+                    let chunks =
+                        (0..body.len()).map(|n| Ok::<_, Error>(Bytes::from(format!("{n},"))));
+                    HttpResponse::Ok()
+                        .content_type("application/json")
+                        .streaming(futures_util::stream::iter(chunks))
+                }),
+            ))
+            .await;
+        let req = actix_web::test::TestRequest::post()
+            .uri("/numbers")
+            .insert_header((header::CONTENT_TYPE, "text/plain"))
+            .set_payload("x".repeat(1000))
+            .to_request();
+        let body = actix_web::test::call_and_read_body(&app, req).await;
+        assert!(body.starts_with(b"0,1,2,"));
+        assert!(body.ends_with(b"998,999,"));
+
+        let output = logs.contents();
+        assert!(output.contains("http.request.body=<text/plain, 1000 bytes>"), "{output}");
+        assert!(
+            output.contains(&format!(
+                "http.response.body=0,1,2,3,4,5,6,7,... ({} bytes total)",
+                body.len()
+            )),
+            "{output}"
+        );
+    }
+}
@@ -0,0 +1,193 @@
+use std::{
+    future::{Future, Ready, ready},
+    pin::Pin,
+    rc::Rc,
+};
+
+use actix_web::{
+    Error,
+    HttpMessage,
+    body::EitherBody,
+    dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready},
+    http::{Method, header},
+};
+
+use crate::controllers::responses::ApiError;
+
+/// Middleware rejecting write requests whose body isn't JSON with `415 Unsupported Media Type`.
+///
+/// Applies to `POST`, `PUT` and `PATCH` requests carrying a body. `application/json` and
+/// `+json` media types are accepted. Paths starting with one of the exempt prefixes are let
+/// through unchecked.
+#[derive(Clone, Debug, Default)]
+pub struct RequireJsonContentType {
+    exempt_paths: Rc<Vec<String>>,
+}
+
+impl RequireJsonContentType {
+    pub fn new(exempt_paths: Vec<String>) -> Self {
+        Self {
+            exempt_paths: Rc::new(exempt_paths),
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for RequireJsonContentType
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Transform = RequireJsonContentTypeMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequireJsonContentTypeMiddleware {
+            service,
+            exempt_paths: self.exempt_paths.clone(),
+        }))
+    }
+}
+
+pub struct RequireJsonContentTypeMiddleware<S> {
+    service: S,
+    exempt_paths: Rc<Vec<String>>,
+}
+
+impl<S> RequireJsonContentTypeMiddleware<S> {
+    fn check(&self, req: &ServiceRequest) -> Result<(), ApiError> {
+        if !matches!(*req.method(), Method::POST | Method::PUT | Method::PATCH) || !has_body(req) {
+            return Ok(());
+        }
+        if self
+            .exempt_paths
+            .iter()
+            .any(|prefix| req.path().starts_with(prefix.as_str()))
+        {
+            return Ok(());
+        }
+
+        match req.mime_type() {
+            | Ok(Some(mime))
+                if mime.essence_str() == "application/json"
+                    || mime.suffix().is_some_and(|suffix| suffix == "json") =>
+            {
+                Ok(())
+            }
+            | Ok(Some(mime)) => Err(ApiError::UnsupportedMediaType(format!(
+                "Expected Content-Type application/json, got {}",
+                mime.essence_str()
+            ))),
+            | _ => Err(ApiError::UnsupportedMediaType(
+                "Expected Content-Type application/json".to_string(),
+            )),
+        }
+    }
+}
+
+impl<S, B> Service<ServiceRequest> for RequireJsonContentTypeMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<EitherBody<B>>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        match self.check(&req) {
+            | Ok(()) => {
+                let fut = self.service.call(req);
+                Box::pin(async move { Ok(fut.await?.map_into_left_body()) })
+            }
+            | Err(e) => Box::pin(ready(Ok(req.error_response(e).map_into_right_body()))),
+        }
+    }
+}
+
+fn has_body(req: &ServiceRequest) -> bool {
+    let headers = req.headers();
+    if headers.contains_key(header::TRANSFER_ENCODING) {
+        return true;
+    }
+
+    headers
+        .get(header::CONTENT_LENGTH)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse::<u64>().ok())
+        .is_some_and(|len| len > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{App, HttpResponse, http::StatusCode, web};
+
+    use super::*;
+
+    async fn ok() -> HttpResponse {
+        HttpResponse::Ok().finish()
+    }
+
+    async fn post(uri: &str, content_type: Option<&str>, body: &'static str) -> ServiceResponse {
+        let app = actix_web::test::init_service(
+            App::new().service(
+                web::scope("/api")
+                    .wrap(RequireJsonContentType::new(vec!["/api/uploads".to_string()]))
+                    .route("/items", web::post().to(ok))
+                    .route("/uploads", web::post().to(ok)),
+            ),
+        )
+        .await;
+
+        let mut req = actix_web::test::TestRequest::post()
+            .uri(uri)
+            .set_payload(body);
+        if let Some(content_type) = content_type {
+            req = req.insert_header((header::CONTENT_TYPE, content_type));
+        }
+        actix_web::test::call_service(&app, req.to_request()).await
+    }
+
+    #[actix_rt::test]
+    async fn test_text_plain_is_rejected() {
+        let resp = post("/api/items", Some("text/plain"), "hello").await;
+        assert_eq!(resp.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+        let body: serde_json::Value = actix_web::test::read_body_json(resp).await;
+        assert_eq!(body["status"], 415);
+        assert_eq!(body["error"], "unsupported_media_type");
+        assert_eq!(body["message"], "Expected Content-Type application/json, got text/plain");
+    }
+
+    #[actix_rt::test]
+    async fn test_missing_content_type_is_rejected() {
+        let resp = post("/api/items", None, "{}").await;
+        assert_eq!(resp.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+    }
+
+    #[actix_rt::test]
+    async fn test_json_passes_through() {
+        let resp = post("/api/items", Some("application/json"), "{}").await;
+        assert_eq!(resp.status(), StatusCode::OK);
+
+        let resp =
+            post("/api/items", Some("application/merge-patch+json; charset=utf-8"), "{}").await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[actix_rt::test]
+    async fn test_empty_body_passes_through() {
+        let resp = post("/api/items", None, "").await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+
+    #[actix_rt::test]
+    async fn test_exempt_paths_pass_through() {
+        let resp = post("/api/uploads", Some("text/plain"), "hello").await;
+        assert_eq!(resp.status(), StatusCode::OK);
+    }
+}
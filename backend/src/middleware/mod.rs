@@ -0,0 +1,13 @@
+pub use access_log::AccessLog;
+pub use admin_auth::RequireAdminToken;
+pub use body_log::BodyLogger;
+pub use json_content_type::RequireJsonContentType;
+pub use security_headers::security_headers;
+pub use slow_request::SlowRequestLogger;
+
+pub mod access_log;
+pub mod admin_auth;
+pub mod body_log;
+pub mod json_content_type;
+pub mod security_headers;
+pub mod slow_request;
@@ -0,0 +1,105 @@
+use actix_web::{
+    http::header::{STRICT_TRANSPORT_SECURITY, X_CONTENT_TYPE_OPTIONS, X_FRAME_OPTIONS},
+    middleware::DefaultHeaders,
+};
+
+use crate::config::AppConfig;
+
+/// Standard security headers added to every response that doesn't set them itself.
+///
+/// `Strict-Transport-Security` is only sent in the `production` environment, so local HTTP
+/// development doesn't get pinned to HTTPS by the browser.
+pub fn security_headers(app: &AppConfig) -> DefaultHeaders {
+    let mut headers = DefaultHeaders::new().add((X_CONTENT_TYPE_OPTIONS, "nosniff"));
+
+    if !app.x_frame_options.is_empty() {
+        headers = headers.add((X_FRAME_OPTIONS, app.x_frame_options.as_str()));
+    }
+
+    if app.environment == "production" && app.hsts_max_age_secs > 0 {
+        headers = headers.add((
+            STRICT_TRANSPORT_SECURITY,
+            format!("max-age={}; includeSubDomains", app.hsts_max_age_secs),
+        ));
+    }
+
+    headers
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{App, HttpResponse, dev::ServiceResponse, web};
+
+    use super::*;
+
+    async fn get(app_config: AppConfig, uri: &str) -> ServiceResponse {
+        let app = actix_web::test::init_service(
+            App::new()
+                .wrap(security_headers(&app_config))
+                .route("/", web::get().to(HttpResponse::Ok))
+                .route(
+                    "/embeddable",
+                    web::get().to(|| async {
+                        HttpResponse::Ok()
+                            .insert_header((X_FRAME_OPTIONS, "SAMEORIGIN"))
+                            .finish()
+                    }),
+                ),
+        )
+        .await;
+        let req = actix_web::test::TestRequest::get().uri(uri).to_request();
+        actix_web::test::call_service(&app, req).await
+    }
+
+    fn config(environment: &str) -> AppConfig {
+        AppConfig {
+            environment: environment.to_string(),
+            x_frame_options: "DENY".to_string(),
+            hsts_max_age_secs: 31_536_000,
+            ..AppConfig::default()
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_headers_in_production() {
+        let resp = get(config("production"), "/").await;
+        let headers = resp.headers();
+
+        assert_eq!(headers.get(X_CONTENT_TYPE_OPTIONS).unwrap(), "nosniff");
+        assert_eq!(headers.get(X_FRAME_OPTIONS).unwrap(), "DENY");
+        assert_eq!(
+            headers.get(STRICT_TRANSPORT_SECURITY).unwrap(),
+            "max-age=31536000; includeSubDomains"
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_hsts_omitted_in_development() {
+        let resp = get(config("development"), "/").await;
+
+        assert_eq!(resp.headers().get(X_CONTENT_TYPE_OPTIONS).unwrap(), "nosniff");
+        assert!(resp.headers().get(STRICT_TRANSPORT_SECURITY).is_none());
+    }
+
+    #[actix_rt::test]
+    async fn test_headers_can_be_disabled() {
+        let resp = get(
+            AppConfig {
+                x_frame_options: String::new(),
+                hsts_max_age_secs: 0,
+                ..config("production")
+            },
+            "/",
+        )
+        .await;
+
+        assert!(resp.headers().get(X_FRAME_OPTIONS).is_none());
+        assert!(resp.headers().get(STRICT_TRANSPORT_SECURITY).is_none());
+    }
+
+    #[actix_rt::test]
+    async fn test_handler_headers_are_kept() {
+        let resp = get(config("production"), "/embeddable").await;
+        assert_eq!(resp.headers().get(X_FRAME_OPTIONS).unwrap(), "SAMEORIGIN");
+    }
+}
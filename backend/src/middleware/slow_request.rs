@@ -0,0 +1,165 @@
+use std::{
+    future::{Future, Ready, ready},
+    pin::Pin,
+    time::{Duration, Instant},
+};
+
+use actix_web::{
+    Error,
+    dev::{Service, ServiceRequest, ServiceResponse, Transform, forward_ready},
+};
+
+/// Middleware that logs a warning for every request taking longer than a threshold.
+///
+/// The warning carries the matched route, method, status code and duration so latency
+/// regressions show up in Kibana as soon as they happen. A threshold of zero disables it.
+#[derive(Clone, Copy, Debug)]
+pub struct SlowRequestLogger {
+    threshold: Duration,
+}
+
+impl SlowRequestLogger {
+    pub fn new(threshold: Duration) -> Self {
+        Self {
+            threshold,
+        }
+    }
+}
+
+impl<S, B> Transform<S, ServiceRequest> for SlowRequestLogger
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = SlowRequestMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(SlowRequestMiddleware {
+            service,
+            threshold: self.threshold,
+        }))
+    }
+}
+
+pub struct SlowRequestMiddleware<S> {
+    service: S,
+    threshold: Duration,
+}
+
+impl<S, B> Service<ServiceRequest> for SlowRequestMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error> + 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Self::Response, Self::Error>>>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let threshold = self.threshold;
+        let started = Instant::now();
+        let fut = self.service.call(req);
+
+        Box::pin(async move {
+            let res = fut.await?;
+            let elapsed = started.elapsed();
+
+            if !threshold.is_zero() && elapsed > threshold {
+                let request = res.request();
+                let route = request
+                    .match_pattern()
+                    .unwrap_or_else(|| request.path().to_string());
+
+                tracing::warn!(
+                    http.method = %request.method(),
+                    http.route = %route,
+                    http.status_code = res.status().as_u16(),
+                    http.response_time_ms = elapsed.as_millis() as u64,
+                    threshold_ms = threshold.as_millis() as u64,
+                    "Slow request"
+                );
+            }
+
+            Ok(res)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{App, HttpResponse, test, web};
+
+    use super::*;
+    use crate::utils::testing::CapturedLogs;
+
+    async fn slow() -> HttpResponse {
+        actix_rt::time::sleep(Duration::from_millis(50)).await;
+        HttpResponse::Ok().finish()
+    }
+
+    async fn fast() -> HttpResponse {
+        HttpResponse::Ok().finish()
+    }
+
+    #[actix_rt::test]
+    async fn test_slow_request_is_logged() {
+        let logs = CapturedLogs::default();
+        let _guard = logs.set_default();
+
+        let app = test::init_service(
+            App::new()
+                .wrap(SlowRequestLogger::new(Duration::from_millis(10)))
+                .route("/slow/{id}", web::get().to(slow)),
+        )
+        .await;
+        let req = test::TestRequest::get().uri("/slow/42").to_request();
+        test::call_service(&app, req).await;
+
+        let output = logs.contents();
+        assert!(output.contains("WARN"));
+        assert!(output.contains("Slow request"));
+        assert!(output.contains("http.route=/slow/{id}"));
+        assert!(output.contains("http.method=GET"));
+        assert!(output.contains("http.status_code=200"));
+    }
+
+    #[actix_rt::test]
+    async fn test_fast_request_is_quiet() {
+        let logs = CapturedLogs::default();
+        let _guard = logs.set_default();
+
+        let app = test::init_service(
+            App::new()
+                .wrap(SlowRequestLogger::new(Duration::from_secs(1)))
+                .route("/fast", web::get().to(fast)),
+        )
+        .await;
+        let req = test::TestRequest::get().uri("/fast").to_request();
+        test::call_service(&app, req).await;
+
+        assert!(!logs.contents().contains("Slow request"));
+    }
+
+    #[actix_rt::test]
+    async fn test_zero_threshold_disables_logging() {
+        let logs = CapturedLogs::default();
+        let _guard = logs.set_default();
+
+        let app = test::init_service(
+            App::new()
+                .wrap(SlowRequestLogger::new(Duration::ZERO))
+                .route("/slow", web::get().to(slow)),
+        )
+        .await;
+        let req = test::TestRequest::get().uri("/slow").to_request();
+        test::call_service(&app, req).await;
+
+        assert!(!logs.contents().contains("Slow request"));
+    }
+}
@@ -1,7 +1,151 @@
-use actix_web::web;
+use actix_web::{
+    FromRequest,
+    Handler,
+    Responder,
+    Scope,
+    dev::HttpServiceFactory,
+    http::Method,
+    web,
+};
 
-use crate::controllers::base;
+use crate::{
+    controllers::{admin, base},
+    middleware::{RequireAdminToken, RequireJsonContentType},
+    state::AppState,
+};
 
-pub fn get() -> actix_web::Scope {
-    web::scope("/api").service(base::health_check)
+/// Mounts handlers on a [`web::ServiceConfig`] and records each one's method and full path.
+///
+/// Every endpoint goes through [`Routes::route`], so the table handed to
+/// [`RouteTable`](crate::utils::routes::RouteTable) is exactly what was mounted.
+struct Routes<'a> {
+    cfg: &'a mut web::ServiceConfig,
+    prefix: String,
+    table: &'a mut Vec<(Method, String)>,
+}
+
+impl Routes<'_> {
+    fn route<F, Args>(&mut self, method: Method, path: &str, handler: F) -> &mut Self
+    where
+        F: Handler<Args>,
+        Args: FromRequest + 'static,
+        F::Output: Responder + 'static,
+    {
+        self.table
+            .push((method.clone(), format!("{}{path}", self.prefix)));
+        self.cfg.route(path, web::method(method).to(handler));
+        self
+    }
+
+    /// A scope at `path` whose routes, added by `mount`, are recorded under it.
+    fn scope(&mut self, path: &str, mount: impl FnOnce(&mut Routes)) -> Scope {
+        let prefix = format!("{}{path}", self.prefix);
+        let table = &mut *self.table;
+        web::scope(path).configure(|cfg| {
+            mount(&mut Routes {
+                cfg,
+                prefix,
+                table,
+            })
+        })
+    }
+
+    fn service(&mut self, factory: impl HttpServiceFactory + 'static) -> &mut Self {
+        self.cfg.service(factory);
+        self
+    }
+}
+
+/// Mounts every endpoint on `cfg` and returns their `(method, path)` pairs for the fallback
+/// handler.
+///
+/// The health endpoints are served both at the root and under `/api`; only the `/api` copies
+/// require a JSON content type. The admin scope is mounted when `enable_admin` is set and
+/// requires `admin_token` as a bearer token.
+pub fn mount(
+    cfg: &mut web::ServiceConfig,
+    enable_admin: bool,
+    admin_token: &str,
+    json_exempt_paths: &[String],
+) -> Vec<(Method, String)> {
+    let mut table = Vec::new();
+    let mut routes = Routes {
+        cfg,
+        prefix: String::new(),
+        table: &mut table,
+    };
+
+    health(&mut routes);
+    let scope = routes.scope("/api", health);
+    routes.service(scope.wrap(RequireJsonContentType::new(json_exempt_paths.to_vec())));
+    if enable_admin {
+        let scope = routes.scope("/admin", admin);
+        routes.service(scope.wrap(RequireAdminToken::new(admin_token.to_string())));
+    }
+
+    table
+}
+
+fn health(routes: &mut Routes) {
+    routes
+        .route(Method::GET, "/", base::health_check)
+        .route(Method::GET, "/health/live", base::liveness)
+        .route(Method::GET, "/health/ready", base::readiness)
+        .route(Method::GET, "/version", base::version)
+        .route(Method::GET, "/ping", base::ping);
+}
+
+/// Operator endpoints, only mounted when `app.enable_admin` is set.
+fn admin(routes: &mut Routes) {
+    routes
+        .route(Method::GET, "/config", admin::show_config)
+        .route(Method::POST, "/seed", admin::reseed::<AppState>);
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{
+        App,
+        http::{StatusCode, header},
+    };
+
+    use super::*;
+    use crate::utils::routes::RouteTable;
+
+    #[actix_rt::test]
+    async fn test_route_table_matches_registered_routes() {
+        let mut routes = Vec::new();
+        let app = actix_web::test::init_service(
+            App::new()
+                .app_data(web::Data::new(AppState::for_tests()))
+                .configure(|cfg| routes = mount(cfg, true, "s3cret", &[])),
+        )
+        .await;
+
+        let table = RouteTable::new(routes, false);
+        for path in ["/", "/health/live", "/api/", "/api/version", "/admin/config"] {
+            assert_eq!(table.allowed_methods(path), vec![Method::GET], "{path}");
+        }
+        assert_eq!(table.allowed_methods("/admin/seed"), vec![Method::POST]);
+
+        for (method, path) in table.routes() {
+            let req = actix_web::test::TestRequest::default()
+                .method(method.clone())
+                .uri(path)
+                .insert_header((header::AUTHORIZATION, "Bearer s3cret"))
+                .to_request();
+            let resp = actix_web::test::call_service(&app, req).await;
+            assert_ne!(resp.status(), StatusCode::NOT_FOUND, "{method} {path}");
+            assert_ne!(resp.status(), StatusCode::UNAUTHORIZED, "{method} {path}");
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_admin_routes_are_only_recorded_when_enabled() {
+        let mut routes = Vec::new();
+        App::new().configure(|cfg| routes = mount(cfg, false, "s3cret", &[]));
+
+        assert!(!routes.is_empty());
+        assert!(routes.iter().all(|(_, path)| !path.starts_with("/admin")));
+    }
 }
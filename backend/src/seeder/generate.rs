@@ -0,0 +1,154 @@
+use std::fmt;
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+/// Upper bound on the rows a single `_generate` spec may produce.
+pub const MAX_GENERATED_ROWS: usize = 10_000;
+
+/// Placeholder replaced by the 1-based row index.
+const INDEX_PLACEHOLDER: &str = "${i}";
+
+/// Contents of a seed file: literal rows, or a spec generating them.
+///
+/// ```json
+/// { "_generate": { "count": 1000, "template": { "email": "user${i}@test.com", "n": "${i}" } } }
+/// ```
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum SeedRows {
+    Rows(Vec<Map<String, Value>>),
+    Generate {
+        #[serde(rename = "_generate")]
+        generate: GenerateSpec,
+    },
+}
+
+impl SeedRows {
+    pub fn into_rows(self) -> Result<Vec<Map<String, Value>>, GenerateError> {
+        match self {
+            | SeedRows::Rows(rows) => Ok(rows),
+            | SeedRows::Generate {
+                generate,
+            } => generate.expand(),
+        }
+    }
+}
+
+/// `count` copies of `template`, with `${i}` in string values replaced by the row index.
+#[derive(Deserialize, Debug)]
+pub struct GenerateSpec {
+    pub count: usize,
+    pub template: Map<String, Value>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct GenerateError {
+    pub count: usize,
+}
+
+impl fmt::Display for GenerateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "_generate count {} exceeds the maximum of {MAX_GENERATED_ROWS} rows", self.count)
+    }
+}
+
+impl std::error::Error for GenerateError {}
+
+impl GenerateSpec {
+    /// Produce the rows, numbered from 1.
+    ///
+    /// A value that is exactly `"${i}"` becomes the number itself, so the index can seed numeric
+    /// columns; anywhere else it is interpolated into the string.
+    pub fn expand(&self) -> Result<Vec<Map<String, Value>>, GenerateError> {
+        if self.count > MAX_GENERATED_ROWS {
+            return Err(GenerateError {
+                count: self.count,
+            });
+        }
+
+        Ok((1..=self.count)
+            .map(|i| {
+                self.template
+                    .iter()
+                    .map(|(column, value)| (column.clone(), interpolate(value, i)))
+                    .collect()
+            })
+            .collect())
+    }
+}
+
+fn interpolate(value: &Value, index: usize) -> Value {
+    match value {
+        | Value::String(s) if s == INDEX_PLACEHOLDER => index.into(),
+        | Value::String(s) => s.replace(INDEX_PLACEHOLDER, &index.to_string()).into(),
+        | Value::Array(values) => values.iter().map(|v| interpolate(v, index)).collect(),
+        | Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(key, v)| (key.clone(), interpolate(v, index)))
+                .collect(),
+        ),
+        | value => value.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn parse(value: Value) -> SeedRows {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn test_generate_expands_with_indices() {
+        let rows = parse(json!({
+            "_generate": {
+                "count": 3,
+                "template": { "email": "user${i}@test.com", "n": "${i}", "active": true },
+            }
+        }))
+        .into_rows()
+        .unwrap();
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!(
+            Value::Object(rows[0].clone()),
+            json!({ "email": "user1@test.com", "n": 1, "active": true })
+        );
+        assert_eq!(
+            Value::Object(rows[2].clone()),
+            json!({ "email": "user3@test.com", "n": 3, "active": true })
+        );
+    }
+
+    #[test]
+    fn test_literal_rows_are_kept() {
+        let rows = parse(json!([{ "id": 1, "name": "${i}" }]))
+            .into_rows()
+            .unwrap();
+        assert_eq!(Value::Object(rows[0].clone()), json!({ "id": 1, "name": "${i}" }));
+    }
+
+    #[test]
+    fn test_count_is_capped() {
+        let spec = GenerateSpec {
+            count: MAX_GENERATED_ROWS + 1,
+            template: Map::new(),
+        };
+        assert_eq!(
+            spec.expand().unwrap_err(),
+            GenerateError {
+                count: MAX_GENERATED_ROWS + 1
+            }
+        );
+
+        let spec = GenerateSpec {
+            count: MAX_GENERATED_ROWS,
+            template: Map::new(),
+        };
+        assert_eq!(spec.expand().unwrap().len(), MAX_GENERATED_ROWS);
+    }
+}
@@ -1,24 +1,239 @@
-use serde_json::Value;
-use sqlx::{Error, QueryBuilder};
-use std::{env, fs};
-use zirv_db_sqlx::get_db_pool;
+use std::{
+    fmt,
+    fs,
+    future::Future,
+    ops::Range,
+    path::{Path, PathBuf},
+};
 
-pub async fn seed_database() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    let pool = get_db_pool!();
+use generate::SeedRows;
+pub use progress::{FileReport, LogProgress, SeedObserver, SeedReport};
+use serde_json::{Map, Value};
+use sqlx::{Error, MySql, MySqlPool, QueryBuilder, Transaction, error::ErrorKind};
+use types::ColumnTypes;
+use validation::SeedMeta;
 
+use crate::config::{ConflictStrategy, SeederConfig};
+
+mod generate;
+mod progress;
+mod types;
+mod validation;
+
+/// Name of the optional per-folder file describing relations between seed files.
+const META_FILE: &str = "_meta.json";
+
+/// Name of the optional per-folder file with SQL type hints for seed columns.
+const TYPES_FILE: &str = "_types.json";
+
+/// Git-ignored seed folder for developer-specific fixtures, applied after the environment's.
+const LOCAL_FOLDER: &str = "local";
+
+/// Column identifying a seed row when a later folder overrides it.
+const ROW_KEY: &str = "id";
+
+/// MySQL's limit on placeholders in a single prepared statement.
+const MAX_BIND_PARAMS: usize = 65_535;
+
+/// Rows loaded from a single seed file.
+#[derive(Debug)]
+pub struct SeedFile {
+    pub path: PathBuf,
+    pub table: String,
+    pub rows: Vec<Map<String, Value>>,
+    /// Statements of a `.sql` script, run instead of inserting `rows`.
+    pub statements: Vec<String>,
+}
+
+/// Seed rows that could not be inserted because they collide with existing rows.
+#[derive(Debug)]
+pub struct SeedConflictError {
+    pub table: String,
+    /// Indices of the rows in the failing chunk.
+    pub rows: Range<usize>,
+    pub source: Error,
+}
+
+impl fmt::Display for SeedConflictError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "seed rows for {} conflict with existing rows ({}): {}",
+            self.table,
+            describe_rows(&self.rows),
+            self.source
+        )
+    }
+}
+
+impl std::error::Error for SeedConflictError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// Seed rows that could not be inserted for a reason other than a conflict, such as an unknown
+/// column or a lost connection.
+#[derive(Debug)]
+pub struct SeedFileError {
+    pub table: String,
+    /// Indices of the rows in the failing chunk.
+    pub rows: Range<usize>,
+    pub source: Error,
+}
+
+impl fmt::Display for SeedFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "inserting seed rows into {} failed ({}): {}",
+            self.table,
+            describe_rows(&self.rows),
+            self.source
+        )
+    }
+}
+
+impl std::error::Error for SeedFileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// A statement of a `.sql` seed script that failed.
+#[derive(Debug)]
+pub struct SeedScriptError {
+    pub path: PathBuf,
+    /// 1-based position of the statement in the script.
+    pub statement: usize,
+    pub source: Error,
+}
+
+impl fmt::Display for SeedScriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "statement {} of {} failed: {}", self.statement, self.path.display(), self.source)
+    }
+}
+
+impl std::error::Error for SeedScriptError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// Runs the seeder's insert statements, one transaction per seed file.
+///
+/// [`MySqlPool`] runs them against the database. A transaction dropped without
+/// [`commit`](Self::commit) is rolled back.
+pub trait SeedExecutor {
+    type Transaction;
+
+    fn begin(&self) -> impl Future<Output = Result<Self::Transaction, Error>>;
+
+    fn execute(
+        &self,
+        txn: &mut Self::Transaction,
+        query: &mut QueryBuilder<'_, MySql>,
+    ) -> impl Future<Output = Result<(), Error>>;
+
+    /// Run a statement of a `.sql` script as written, returning the number of affected rows.
+    ///
+    /// Scripts go through the text protocol, as MySQL can't prepare every statement a script
+    /// may hold.
+    fn execute_script(
+        &self,
+        txn: &mut Self::Transaction,
+        statement: &str,
+    ) -> impl Future<Output = Result<u64, Error>>;
+
+    fn commit(&self, txn: Self::Transaction) -> impl Future<Output = Result<(), Error>>;
+}
+
+impl SeedExecutor for MySqlPool {
+    type Transaction = Transaction<'static, MySql>;
+
+    async fn begin(&self) -> Result<Self::Transaction, Error> {
+        MySqlPool::begin(self).await
+    }
+
+    async fn execute(
+        &self,
+        txn: &mut Self::Transaction,
+        query: &mut QueryBuilder<'_, MySql>,
+    ) -> Result<(), Error> {
+        query.build().execute(&mut **txn).await.map(|_| ())
+    }
+
+    async fn execute_script(
+        &self,
+        txn: &mut Self::Transaction,
+        statement: &str,
+    ) -> Result<u64, Error> {
+        sqlx::raw_sql(statement)
+            .execute(&mut **txn)
+            .await
+            .map(|result| result.rows_affected())
+    }
+
+    async fn commit(&self, txn: Self::Transaction) -> Result<(), Error> {
+        txn.commit().await
+    }
+}
+
+/// Seed `pool` from `src/seeder/data` for `environment`, usually `app.environment`.
+///
+/// Progress is logged every `config.progress_interval` rows.
+pub async fn seed_database(
+    pool: &MySqlPool,
+    environment: &str,
+    config: &SeederConfig,
+) -> Result<SeedReport, Box<dyn std::error::Error + Send + Sync>> {
     let current_dir = std::env::current_dir()?;
     let data_dir = current_dir.join("src").join("seeder").join("data");
 
-    for folder in &[
-        "default",
-        env::var("ENV")
-            .unwrap_or_else(|_| "development".to_string())
-            .as_str(),
-    ] {
+    let mut progress = LogProgress::new(config.progress_interval);
+    seed_from_dir(pool, &data_dir, environment, config, &mut progress).await
+}
+
+/// Insert the seed files of `data_dir` through `executor`, reporting progress to `observer`.
+///
+/// Files are read from the `default` folder, then the one named after `environment`, then the
+/// git-ignored `local` folder for developer fixtures. A later folder's rows replace earlier rows
+/// of the same table with the same `id`. A missing environment folder is an error when
+/// `config.require_env_dir` is set, and a warning otherwise.
+///
+/// Within a folder, JSON files and `.sql` scripts run in the order of their numeric file name
+/// prefix, so `2_roles.sql` runs after `1_users.json` and before `10_orders.json`.
+pub async fn seed_from_dir(
+    executor: &impl SeedExecutor,
+    data_dir: &Path,
+    environment: &str,
+    config: &SeederConfig,
+    observer: &mut impl SeedObserver,
+) -> Result<SeedReport, Box<dyn std::error::Error + Send + Sync>> {
+    let mut files = Vec::new();
+    let mut meta = SeedMeta::default();
+    let mut types = ColumnTypes::default();
+
+    for folder in ["default", environment, LOCAL_FOLDER] {
         let dir_path = data_dir.join(folder);
 
         if !dir_path.exists() {
-            println!("Directory does not exist: {}", dir_path.display());
+            if folder != environment {
+                tracing::debug!(directory = %dir_path.display(), "Seed directory does not exist");
+            } else if config.require_env_dir {
+                return Err(format!(
+                    "seed directory for environment {environment} does not exist: {}",
+                    dir_path.display()
+                )
+                .into());
+            } else {
+                tracing::warn!(
+                    environment,
+                    directory = %dir_path.display(),
+                    "Seed directory for the environment does not exist, seeding defaults only"
+                );
+            }
             continue;
         }
 
@@ -26,100 +241,936 @@ pub async fn seed_database() -> Result<(), Box<dyn std::error::Error + Send + Sy
             return Err("data folder is not a directory".into());
         }
 
-        for entry in fs::read_dir(&dir_path)? {
-            let entry = entry?;
-            let path = entry.path();
+        let meta_path = dir_path.join(META_FILE);
+        if meta_path.exists() {
+            meta.merge(SeedMeta::load(&meta_path)?);
+        }
 
-            if path.extension().and_then(|s| s.to_str()) != Some("json") {
-                continue;
+        let types_path = dir_path.join(TYPES_FILE);
+        if types_path.exists() {
+            types.merge(ColumnTypes::load(&types_path)?);
+        }
+
+        let folder_files = load_seed_files(&dir_path)?;
+        let names: Vec<String> = folder_files
+            .iter()
+            .filter_map(|file| file.path.file_name())
+            .map(|name| name.to_string_lossy().into_owned())
+            .collect();
+        tracing::info!(
+            directory = %dir_path.display(),
+            files = %names.join(", "),
+            "Resolved seed directory"
+        );
+
+        overlay(&mut files, folder_files);
+    }
+
+    // Report dangling references up front instead of failing halfway through the inserts
+    validation::validate_foreign_keys(&files, &meta)?;
+
+    let mut report = SeedReport::default();
+    for file in &files {
+        if file.rows.is_empty() && file.statements.is_empty() {
+            continue;
+        }
+
+        println!("Processing file: {}", file.path.display());
+        observer.on_file_start(file);
+
+        if !file.statements.is_empty() {
+            let rows = run_script(executor, file, observer)
+                .await
+                .inspect_err(|_| observer.on_file_failed(file))?;
+            let file_report = FileReport {
+                table: file.table.clone(),
+                rows,
+            };
+            observer.on_file_done(&file_report);
+            report.files.push(file_report);
+            continue;
+        }
+
+        let result = insert_file(executor, file, config, &types, observer).await;
+        if result.is_err() {
+            observer.on_file_failed(file);
+        }
+
+        let rows = match result {
+            | Ok(rows) => rows,
+            | Err((rows, e)) if is_conflict(&e) => {
+                on_conflict(config.conflict_strategy, &file.table, rows, e)?;
+                0
             }
+            | Err((rows, source)) => {
+                return Err(SeedFileError {
+                    table: file.table.clone(),
+                    rows,
+                    source,
+                }
+                .into());
+            }
+        };
+
+        let file_report = FileReport {
+            table: file.table.clone(),
+            rows,
+        };
+        observer.on_file_done(&file_report);
+        report.files.push(file_report);
+    }
+
+    Ok(report)
+}
+
+/// Add the files of a later seed folder to `files`.
+///
+/// Rows of a table already seeded replace the rows with the same [`ROW_KEY`] and are appended
+/// otherwise; files of new tables and scripts are appended as they are.
+fn overlay(files: &mut Vec<SeedFile>, later: Vec<SeedFile>) {
+    for file in later {
+        let existing = files
+            .iter_mut()
+            .find(|f| f.table == file.table && f.statements.is_empty());
+        let Some(existing) = existing.filter(|_| file.statements.is_empty()) else {
+            files.push(file);
+            continue;
+        };
 
-            println!("Processing file: {}", path.display());
-
-            let table_name = path
-                .file_stem()
-                .and_then(|s| {
-                    s.to_str().map(|s| match s.starts_with("1_") {
-                        | true => s[2..].to_string(),
-                        | false => s.to_string(),
-                    })
-                })
-                .ok_or("invalid filename")?;
-
-            let raw = fs::read_to_string(&path)?;
-            let rows: Vec<serde_json::Map<String, Value>> = serde_json::from_str(&raw)?;
-            if rows.is_empty() {
-                continue;
+        for row in file.rows {
+            let replaced = row.get(ROW_KEY).and_then(|key| {
+                existing
+                    .rows
+                    .iter_mut()
+                    .find(|r| r.get(ROW_KEY) == Some(key))
+            });
+            match replaced {
+                | Some(existing_row) => *existing_row = row,
+                | None => existing.rows.push(row),
             }
+        }
+    }
+}
 
-            let columns: Vec<String> = rows[0].keys().cloned().collect();
+/// Insert the rows of `file` in chunks, all within one transaction.
+///
+/// Returns the number of inserted rows, or the row range of the failing chunk with its error.
+async fn insert_file<E: SeedExecutor>(
+    executor: &E,
+    file: &SeedFile,
+    config: &SeederConfig,
+    types: &ColumnTypes,
+    observer: &mut impl SeedObserver,
+) -> Result<usize, (Range<usize>, Error)> {
+    let all_rows = 0..file.rows.len();
+    let chunk_size = chunk_size(file.rows[0].len(), config.chunk_rows);
 
-            let mut qb = QueryBuilder::new(format!("INSERT INTO {} ", table_name));
-            qb.push("(");
-            for (i, col) in columns.iter().enumerate() {
-                qb.push(col);
-                if i + 1 < columns.len() {
-                    qb.push(", ");
-                }
+    let mut txn = executor.begin().await.map_err(|e| (all_rows.clone(), e))?;
+    for (i, chunk) in file.rows.chunks(chunk_size).enumerate() {
+        let start = i * chunk_size;
+        let mut qb = insert_query(&file.table, chunk, config.conflict_strategy, types);
+        executor
+            .execute(&mut txn, &mut qb)
+            .await
+            .map_err(|e| (start..start + chunk.len(), e))?;
+        observer.on_rows_inserted(chunk.len());
+    }
+    executor.commit(txn).await.map_err(|e| (all_rows, e))?;
+
+    Ok(file.rows.len())
+}
+
+/// Run the statements of a `.sql` seed file in one transaction, returning the affected rows.
+///
+/// Scripts run as written, so the conflict strategy doesn't apply: a failing statement rolls the
+/// script back and stops seeding.
+async fn run_script<E: SeedExecutor>(
+    executor: &E,
+    file: &SeedFile,
+    observer: &mut impl SeedObserver,
+) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+    let mut affected = 0;
+    let mut txn = executor.begin().await?;
+    for (i, statement) in file.statements.iter().enumerate() {
+        let rows = executor
+            .execute_script(&mut txn, statement)
+            .await
+            .map_err(|source| SeedScriptError {
+                path: file.path.clone(),
+                statement: i + 1,
+                source,
+            })? as usize;
+        if rows > 0 {
+            observer.on_rows_inserted(rows);
+        }
+        affected += rows;
+    }
+    executor.commit(txn).await?;
+
+    Ok(affected)
+}
+
+/// Split a SQL script into its statements on `;`, dropping empty and comment-only ones.
+///
+/// The split is naive: a `;` inside a string literal also ends a statement.
+fn split_statements(script: &str) -> Vec<String> {
+    script
+        .split(';')
+        .map(str::trim)
+        .filter(|statement| {
+            statement.lines().any(|line| {
+                let line = line.trim();
+                !line.is_empty() && !line.starts_with("--")
+            })
+        })
+        .map(str::to_string)
+        .collect()
+}
+
+/// Rows per insert statement: `chunk_rows`, lowered so that a statement binds at most
+/// [`MAX_BIND_PARAMS`] values.
+fn chunk_size(columns: usize, chunk_rows: usize) -> usize {
+    (MAX_BIND_PARAMS / columns.max(1)).min(chunk_rows).max(1)
+}
+
+/// Human readable, 1-based description of a row range.
+fn describe_rows(rows: &Range<usize>) -> String {
+    format!("rows {}-{}", rows.start + 1, rows.end)
+}
+
+/// Build the multi-row `INSERT` of `rows` into `table_name`.
+///
+/// Values of columns with a type hint are bound as `CAST(? AS <type>)`. With
+/// [`ConflictStrategy::Upsert`] rows colliding on a unique key overwrite the existing row.
+fn insert_query<'a>(
+    table_name: &str,
+    rows: &'a [Map<String, Value>],
+    strategy: ConflictStrategy,
+    types: &ColumnTypes,
+) -> QueryBuilder<'a, MySql> {
+    let columns: Vec<String> = rows[0].keys().cloned().collect();
+
+    let mut qb = QueryBuilder::new(format!("INSERT INTO {} ", table_name));
+    qb.push("(");
+    for (i, col) in columns.iter().enumerate() {
+        qb.push(col);
+        if i + 1 < columns.len() {
+            qb.push(", ");
+        }
+    }
+    qb.push(") VALUES ");
+
+    for (ri, row) in rows.iter().enumerate() {
+        qb.push("(");
+        for (ci, col) in columns.iter().enumerate() {
+            let val = row.get(col).unwrap_or(&Value::Null);
+            let sql_type = types.get(col);
+            if sql_type.is_some() {
+                qb.push("CAST(");
             }
-            qb.push(") VALUES ");
-
-            for (ri, row) in rows.iter().enumerate() {
-                qb.push("(");
-                for (ci, col) in columns.iter().enumerate() {
-                    let val = row.get(col).unwrap_or(&Value::Null);
-                    match val {
-                        | Value::Null => {
-                            // Bind NULL
-                            qb.push_bind(None::<String>);
-                        }
-                        | Value::Bool(b) => {
-                            qb.push_bind(*b);
-                        }
-                        | Value::Number(n) if n.is_i64() => {
-                            qb.push_bind(n.as_i64().unwrap());
-                        }
-                        | Value::Number(n) if n.is_f64() => {
-                            qb.push_bind(n.as_f64().unwrap());
-                        }
-                        | Value::Number(n) => {
-                            qb.push_bind(n.to_string());
-                        }
-                        | Value::String(s) => {
-                            qb.push_bind(s);
-                        }
-                        | other => {
-                            qb.push_bind(other.to_string());
-                        }
-                    }
-
-                    if ci + 1 < columns.len() {
-                        qb.push(", ");
-                    }
+            match val {
+                | Value::Null => {
+                    // Bind NULL
+                    qb.push_bind(None::<String>);
                 }
-                qb.push(")");
-
-                if ri + 1 < rows.len() {
-                    qb.push(", ");
+                | Value::Bool(b) => {
+                    qb.push_bind(*b);
+                }
+                | Value::Number(n) if n.is_i64() => {
+                    qb.push_bind(n.as_i64().unwrap());
+                }
+                | Value::Number(n) if n.is_f64() => {
+                    qb.push_bind(n.as_f64().unwrap());
+                }
+                | Value::Number(n) => {
+                    qb.push_bind(n.to_string());
                 }
+                | Value::String(s) => {
+                    qb.push_bind(s);
+                }
+                | other => {
+                    qb.push_bind(other.to_string());
+                }
+            }
+            if let Some(sql_type) = sql_type {
+                qb.push(format!(" AS {sql_type})"));
             }
 
-            match qb.build().execute(pool).await {
-                | Ok(_) => {}
-                | Err(e) => {
-                    if let Error::Database(db_err) = &e {
-                        if let Some(code) = db_err.code() {
-                            if code.starts_with("23") {
-                                return Ok(());
-                            }
-                        }
-                    }
-                    eprintln!("Skipping inserting into {}: {}", table_name, e);
-                    return Ok(());
-                }
-            };
+            if ci + 1 < columns.len() {
+                qb.push(", ");
+            }
+        }
+        qb.push(")");
+
+        if ri + 1 < rows.len() {
+            qb.push(", ");
         }
     }
 
-    Ok(())
+    if strategy == ConflictStrategy::Upsert {
+        qb.push(" AS new ON DUPLICATE KEY UPDATE ");
+        for (i, col) in columns.iter().enumerate() {
+            qb.push(format!("{col} = new.{col}"));
+            if i + 1 < columns.len() {
+                qb.push(", ");
+            }
+        }
+    }
+
+    qb
+}
+
+/// Whether an insert failed because a row with the same unique key already exists.
+///
+/// MySQL reports every integrity error as SQLSTATE `23000`, so foreign key and `NOT NULL`
+/// failures are told apart by the error kind and are not conflicts.
+fn is_conflict(e: &Error) -> bool {
+    matches!(e, Error::Database(db_err) if db_err.kind() == ErrorKind::UniqueViolation)
+}
+
+/// Apply the conflict strategy to a failed insert: `Ok` to carry on with the next file.
+fn on_conflict(
+    strategy: ConflictStrategy,
+    table: &str,
+    rows: Range<usize>,
+    e: Error,
+) -> Result<(), SeedConflictError> {
+    match strategy {
+        | ConflictStrategy::Skip => {
+            tracing::warn!(
+                table,
+                rows = %describe_rows(&rows),
+                error = %e,
+                "Skipping seed rows that already exist"
+            );
+            Ok(())
+        }
+        // An upsert only fails on conflicts it can't resolve, such as foreign keys
+        | ConflictStrategy::Abort | ConflictStrategy::Upsert => Err(SeedConflictError {
+            table: table.to_string(),
+            rows,
+            source: e,
+        }),
+    }
+}
+
+/// Load every JSON seed file and `.sql` script of a folder, ordered by [`order_key`].
+///
+/// Files starting with `_` hold seeder metadata rather than table rows and are skipped. A JSON
+/// file holds either an array of rows or a `_generate` spec expanding into rows.
+fn load_seed_files(
+    dir_path: &Path,
+) -> Result<Vec<SeedFile>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut paths = Vec::new();
+    for entry in fs::read_dir(dir_path)? {
+        let path = entry?.path();
+
+        if !matches!(path.extension().and_then(|s| s.to_str()), Some("json" | "sql")) {
+            continue;
+        }
+        if path
+            .file_name()
+            .and_then(|s| s.to_str())
+            .is_some_and(|s| s.starts_with('_'))
+        {
+            continue;
+        }
+
+        paths.push(path);
+    }
+    paths.sort_by_cached_key(|path| order_key(path));
+
+    let mut files = Vec::with_capacity(paths.len());
+    for path in paths {
+        let table = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .map(|s| order_key(Path::new(s)).1)
+            .ok_or("invalid filename")?;
+
+        let raw = fs::read_to_string(&path)?;
+        let (rows, statements) = match path.extension().and_then(|s| s.to_str()) {
+            | Some("sql") => (Vec::new(), split_statements(&raw)),
+            | _ => (serde_json::from_str::<SeedRows>(&raw)?.into_rows()?, Vec::new()),
+        };
+
+        files.push(SeedFile {
+            path,
+            table,
+            rows,
+            statements,
+        });
+    }
+
+    Ok(files)
+}
+
+/// Sort key of a seed file: its numeric `<n>_` prefix and the rest of its name.
+///
+/// Files without a prefix sort after prefixed ones, by name.
+fn order_key(path: &Path) -> (u64, String) {
+    let name = path
+        .file_name()
+        .and_then(|s| s.to_str())
+        .unwrap_or_default();
+    name.split_once('_')
+        .and_then(|(prefix, rest)| Some((prefix.parse().ok()?, rest.to_string())))
+        .unwrap_or((u64::MAX, name.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, env};
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::utils::testing::database_error;
+
+    /// Executor recording each statement, failing every insert after the first `succeed` ones
+    /// with `error_number` when set.
+    #[derive(Default)]
+    struct RecordingExecutor {
+        statements: RefCell<Vec<String>>,
+        error_number: Option<u16>,
+        succeed: usize,
+    }
+
+    impl RecordingExecutor {
+        fn inserts(&self) -> Vec<String> {
+            self.statements
+                .borrow()
+                .iter()
+                .filter(|sql| sql.starts_with("INSERT"))
+                .cloned()
+                .collect()
+        }
+    }
+
+    impl SeedExecutor for RecordingExecutor {
+        type Transaction = ();
+
+        async fn begin(&self) -> Result<(), Error> {
+            self.statements.borrow_mut().push("BEGIN".to_string());
+            Ok(())
+        }
+
+        async fn execute(
+            &self,
+            _: &mut (),
+            query: &mut QueryBuilder<'_, MySql>,
+        ) -> Result<(), Error> {
+            let inserted = self.inserts().len();
+            self.statements.borrow_mut().push(query.sql().to_string());
+            match self.error_number {
+                | Some(number) if inserted >= self.succeed => Err(database_error(number, None)),
+                | _ => Ok(()),
+            }
+        }
+
+        async fn execute_script(&self, _: &mut (), statement: &str) -> Result<u64, Error> {
+            self.statements.borrow_mut().push(statement.to_string());
+            Ok(1)
+        }
+
+        async fn commit(&self, _: ()) -> Result<(), Error> {
+            self.statements.borrow_mut().push("COMMIT".to_string());
+            Ok(())
+        }
+    }
+
+    /// Observer recording the hooks it receives, in order.
+    #[derive(Default)]
+    struct RecordingObserver {
+        events: Vec<String>,
+    }
+
+    impl SeedObserver for RecordingObserver {
+        fn on_file_start(&mut self, file: &SeedFile) {
+            self.events.push(format!("start {}", file.table));
+        }
+
+        fn on_rows_inserted(&mut self, count: usize) {
+            self.events.push(format!("inserted {count}"));
+        }
+
+        fn on_file_done(&mut self, report: &FileReport) {
+            self.events
+                .push(format!("done {} ({} rows)", report.table, report.rows));
+        }
+
+        fn on_file_failed(&mut self, file: &SeedFile) {
+            self.events.push(format!("failed {}", file.table));
+        }
+    }
+
+    /// Write a seed data folder holding `default/users.json` and `test/orders.json`.
+    fn write_data_dir(name: &str) -> PathBuf {
+        let data_dir = env::temp_dir().join(format!("{}-{name}", std::process::id()));
+        fs::create_dir_all(data_dir.join("default")).unwrap();
+        fs::create_dir_all(data_dir.join("test")).unwrap();
+        fs::write(data_dir.join("default").join("users.json"), r#"[{ "id": 1, "name": "Ada" }]"#)
+            .unwrap();
+        fs::write(data_dir.join("test").join("orders.json"), r#"[{ "id": 7, "user_id": 1 }]"#)
+            .unwrap();
+        data_dir
+    }
+
+    fn file() -> SeedFile {
+        SeedFile {
+            path: "users.json".into(),
+            table: "users".to_string(),
+            rows: serde_json::from_value(json!([{ "id": 1, "name": "Ada" }])).unwrap(),
+            statements: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_only_integrity_errors_are_conflicts() {
+        assert!(is_conflict(&database_error(1062, None)));
+        assert!(!is_conflict(&database_error(1452, None)));
+        assert!(!is_conflict(&database_error(1048, None)));
+        assert!(!is_conflict(&database_error(1146, None)));
+        assert!(!is_conflict(&Error::PoolTimedOut));
+    }
+
+    #[test]
+    fn test_skip_continues() {
+        let result = on_conflict(ConflictStrategy::Skip, "users", 0..1, database_error(1062, None));
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_abort_returns_error() {
+        let err =
+            on_conflict(ConflictStrategy::Abort, "users", 1000..2000, database_error(1062, None))
+                .unwrap_err();
+        assert_eq!(err.table, "users");
+        assert!(err.to_string().starts_with("seed rows for users conflict"));
+        assert!(err.to_string().contains("(rows 1001-2000)"));
+    }
+
+    #[test]
+    fn test_upsert_updates_on_duplicate_key() {
+        let file = file();
+
+        let sql = insert_query(
+            &file.table,
+            &file.rows,
+            ConflictStrategy::Upsert,
+            &ColumnTypes::default(),
+        )
+        .into_sql();
+        assert_eq!(
+            sql,
+            "INSERT INTO users (id, name) VALUES (?, ?) \
+             AS new ON DUPLICATE KEY UPDATE id = new.id, name = new.name"
+        );
+
+        let sql =
+            insert_query(&file.table, &file.rows, ConflictStrategy::Skip, &ColumnTypes::default())
+                .into_sql();
+        assert_eq!(sql, "INSERT INTO users (id, name) VALUES (?, ?)");
+
+        // A conflict the upsert could not resolve is still reported
+        let result =
+            on_conflict(ConflictStrategy::Upsert, "users", 0..1, database_error(1062, None));
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_type_hints_cast_binds() {
+        let file = SeedFile {
+            path: "orders.json".into(),
+            table: "orders".to_string(),
+            rows: serde_json::from_value(json!([
+                { "id": 1, "created_at": "2025-01-01T00:00:00Z", "status": "paid" },
+                { "id": 2, "created_at": "2025-01-02T00:00:00Z", "status": "open" },
+            ]))
+            .unwrap(),
+            statements: Vec::new(),
+        };
+        let types: ColumnTypes =
+            serde_json::from_value(json!({ "created_at": "DATETIME", "status": "CHAR(8)" }))
+                .unwrap();
+
+        let sql = insert_query(&file.table, &file.rows, ConflictStrategy::Skip, &types).into_sql();
+        assert_eq!(
+            sql,
+            "INSERT INTO orders (created_at, id, status) VALUES \
+             (CAST(? AS DATETIME), ?, CAST(? AS CHAR(8))), \
+             (CAST(? AS DATETIME), ?, CAST(? AS CHAR(8)))"
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_seeds_default_and_environment_folders() {
+        let data_dir = write_data_dir("seed-folders");
+        let executor = RecordingExecutor::default();
+        let mut observer = RecordingObserver::default();
+
+        let result =
+            seed_from_dir(&executor, &data_dir, "test", &SeederConfig::default(), &mut observer)
+                .await;
+        fs::remove_dir_all(data_dir).unwrap();
+
+        assert_eq!(result.unwrap().total_rows(), 2);
+        assert_eq!(
+            *executor.statements.borrow(),
+            [
+                "BEGIN",
+                "INSERT INTO users (id, name) VALUES (?, ?)",
+                "COMMIT",
+                "BEGIN",
+                "INSERT INTO orders (id, user_id) VALUES (?, ?)",
+                "COMMIT",
+            ]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_conflicting_seed_aborts() {
+        let data_dir = write_data_dir("seed-abort");
+        let executor = RecordingExecutor {
+            error_number: Some(1062),
+            ..Default::default()
+        };
+        let config = SeederConfig {
+            conflict_strategy: ConflictStrategy::Abort,
+            ..Default::default()
+        };
+
+        let result =
+            seed_from_dir(&executor, &data_dir, "test", &config, &mut RecordingObserver::default())
+                .await;
+        fs::remove_dir_all(data_dir).unwrap();
+
+        let err = result.unwrap_err();
+        assert_eq!(err.downcast_ref::<SeedConflictError>().unwrap().table, "users");
+        assert_eq!(executor.inserts().len(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn test_observer_receives_progress_events() {
+        let data_dir = write_data_dir("seed-observer");
+        let executor = RecordingExecutor::default();
+        let mut observer = RecordingObserver::default();
+
+        let result =
+            seed_from_dir(&executor, &data_dir, "test", &SeederConfig::default(), &mut observer)
+                .await;
+        fs::remove_dir_all(data_dir).unwrap();
+
+        result.unwrap();
+        assert_eq!(
+            observer.events,
+            [
+                "start users",
+                "inserted 1",
+                "done users (1 rows)",
+                "start orders",
+                "inserted 1",
+                "done orders (1 rows)",
+            ]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_skipped_conflicts_report_no_rows() {
+        let data_dir = write_data_dir("seed-observer-skip");
+        let executor = RecordingExecutor {
+            error_number: Some(1062),
+            ..Default::default()
+        };
+        let mut observer = RecordingObserver::default();
+
+        let result =
+            seed_from_dir(&executor, &data_dir, "test", &SeederConfig::default(), &mut observer)
+                .await;
+        fs::remove_dir_all(data_dir).unwrap();
+
+        assert_eq!(result.unwrap().total_rows(), 0);
+        assert_eq!(
+            observer.events,
+            [
+                "start users",
+                "failed users",
+                "done users (0 rows)",
+                "start orders",
+                "failed orders",
+                "done orders (0 rows)",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_chunk_size_respects_bind_limit() {
+        assert_eq!(chunk_size(2, 1000), 1000);
+        assert_eq!(chunk_size(100, 1000), 655);
+        assert_eq!(chunk_size(100_000, 1000), 1);
+    }
+
+    #[actix_rt::test]
+    async fn test_large_files_are_inserted_in_chunks() {
+        let data_dir = env::temp_dir().join(format!("{}-seed-chunks", std::process::id()));
+        fs::create_dir_all(data_dir.join("default")).unwrap();
+        let generate = json!({
+            "_generate": {
+                "count": 2500,
+                "template": { "id": "${i}", "email": "user${i}@test.com" },
+            }
+        });
+        fs::write(data_dir.join("default").join("users.json"), generate.to_string()).unwrap();
+        let executor = RecordingExecutor::default();
+        let mut observer = RecordingObserver::default();
+
+        let result =
+            seed_from_dir(&executor, &data_dir, "test", &SeederConfig::default(), &mut observer)
+                .await;
+        fs::remove_dir_all(data_dir).unwrap();
+
+        assert_eq!(result.unwrap().total_rows(), 2500);
+        let rows_per_insert: Vec<usize> = executor
+            .inserts()
+            .iter()
+            .map(|sql| sql.matches("(?, ?)").count())
+            .collect();
+        assert_eq!(rows_per_insert, [1000, 1000, 500]);
+        assert_eq!(
+            executor
+                .statements
+                .borrow()
+                .iter()
+                .filter(|sql| *sql == "COMMIT")
+                .count(),
+            1
+        );
+        assert_eq!(
+            observer.events,
+            [
+                "start users",
+                "inserted 1000",
+                "inserted 1000",
+                "inserted 500",
+                "done users (2500 rows)"
+            ]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_failing_chunk_is_identified() {
+        let data_dir = env::temp_dir().join(format!("{}-seed-chunk-error", std::process::id()));
+        fs::create_dir_all(data_dir.join("default")).unwrap();
+        let generate = json!({ "_generate": { "count": 25, "template": { "id": "${i}" } } });
+        fs::write(data_dir.join("default").join("users.json"), generate.to_string()).unwrap();
+        let executor = RecordingExecutor {
+            error_number: Some(1062),
+            succeed: 1,
+            ..Default::default()
+        };
+        let config = SeederConfig {
+            conflict_strategy: ConflictStrategy::Abort,
+            chunk_rows: 10,
+            ..Default::default()
+        };
+
+        let result =
+            seed_from_dir(&executor, &data_dir, "test", &config, &mut RecordingObserver::default())
+                .await;
+        fs::remove_dir_all(data_dir).unwrap();
+
+        let err = result.unwrap_err();
+        let err = err.downcast_ref::<SeedConflictError>().unwrap();
+        assert_eq!(err.rows, 10..20);
+        assert!(!executor.statements.borrow().contains(&"COMMIT".to_string()));
+    }
+
+    #[actix_rt::test]
+    async fn test_other_insert_errors_fail_seeding() {
+        let data_dir = write_data_dir("seed-insert-error");
+        // Unknown table, which no conflict strategy skips
+        let executor = RecordingExecutor {
+            error_number: Some(1146),
+            ..Default::default()
+        };
+
+        let result = seed_from_dir(
+            &executor,
+            &data_dir,
+            "test",
+            &SeederConfig::default(),
+            &mut RecordingObserver::default(),
+        )
+        .await;
+        fs::remove_dir_all(data_dir).unwrap();
+
+        let err = result.unwrap_err();
+        let err = err.downcast_ref::<SeedFileError>().unwrap();
+        assert_eq!(err.table, "users");
+        assert_eq!(err.rows, 0..1);
+        assert_eq!(executor.inserts().len(), 1);
+    }
+
+    fn users(path: &str, rows: Value) -> SeedFile {
+        SeedFile {
+            path: path.into(),
+            table: "users".to_string(),
+            rows: serde_json::from_value(rows).unwrap(),
+            statements: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_later_folders_override_rows_by_id() {
+        let mut files = vec![users(
+            "default/users.json",
+            json!([{ "id": 1, "name": "Ada" }, { "id": 2, "name": "Grace" }]),
+        )];
+
+        overlay(&mut files, vec![users("test/users.json", json!([{ "id": 2, "name": "Hopper" }]))]);
+        overlay(
+            &mut files,
+            vec![
+                users(
+                    "local/users.json",
+                    json!([{ "id": 1, "name": "Me" }, { "id": 3, "name": "Alan" }]),
+                ),
+                SeedFile {
+                    path: "local/orders.json".into(),
+                    table: "orders".to_string(),
+                    rows: serde_json::from_value(json!([{ "id": 7, "user_id": 3 }])).unwrap(),
+                    statements: Vec::new(),
+                },
+            ],
+        );
+
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].path, PathBuf::from("default/users.json"));
+        assert_eq!(
+            Value::Array(files[0].rows.iter().cloned().map(Value::Object).collect()),
+            json!([
+                { "id": 1, "name": "Me" },
+                { "id": 2, "name": "Hopper" },
+                { "id": 3, "name": "Alan" },
+            ])
+        );
+        assert_eq!(files[1].table, "orders");
+    }
+
+    #[actix_rt::test]
+    async fn test_local_folder_is_seeded_last() {
+        let data_dir = write_data_dir("seed-local");
+        fs::create_dir_all(data_dir.join("local")).unwrap();
+        fs::write(
+            data_dir.join("local").join("users.json"),
+            r#"[{ "id": 1, "name": "Me" }, { "id": 2, "name": "Alan" }]"#,
+        )
+        .unwrap();
+        let executor = RecordingExecutor::default();
+        let mut observer = RecordingObserver::default();
+
+        let result =
+            seed_from_dir(&executor, &data_dir, "test", &SeederConfig::default(), &mut observer)
+                .await;
+        fs::remove_dir_all(data_dir).unwrap();
+
+        assert_eq!(result.unwrap().total_rows(), 3);
+        assert_eq!(
+            executor.inserts(),
+            [
+                "INSERT INTO users (id, name) VALUES (?, ?), (?, ?)",
+                "INSERT INTO orders (id, user_id) VALUES (?, ?)",
+            ]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn test_missing_environment_folder() {
+        let data_dir = write_data_dir("seed-missing-env");
+        let executor = RecordingExecutor::default();
+
+        let result = seed_from_dir(
+            &executor,
+            &data_dir,
+            "staging",
+            &SeederConfig::default(),
+            &mut RecordingObserver::default(),
+        )
+        .await;
+        assert_eq!(result.unwrap().total_rows(), 1);
+
+        let config = SeederConfig {
+            require_env_dir: true,
+            ..Default::default()
+        };
+        let result = seed_from_dir(
+            &executor,
+            &data_dir,
+            "staging",
+            &config,
+            &mut RecordingObserver::default(),
+        )
+        .await;
+        fs::remove_dir_all(data_dir).unwrap();
+
+        let err = result.unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("seed directory for environment staging does not exist")
+        );
+        assert_eq!(executor.inserts().len(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn test_sql_script_runs_every_statement() {
+        let data_dir = env::temp_dir().join(format!("{}-seed-sql-script", std::process::id()));
+        fs::create_dir_all(data_dir.join("default")).unwrap();
+        fs::write(
+            data_dir.join("default").join("2_roles.sql"),
+            "INSERT INTO roles (name) VALUES ('admin');\n\
+             UPDATE users SET role = 'admin' WHERE id = 1;\n\
+             -- done\n",
+        )
+        .unwrap();
+
+        let executor = RecordingExecutor::default();
+        let report = seed_from_dir(
+            &executor,
+            &data_dir,
+            "test",
+            &SeederConfig::default(),
+            &mut RecordingObserver::default(),
+        )
+        .await;
+        fs::remove_dir_all(data_dir).unwrap();
+
+        assert_eq!(report.unwrap().total_rows(), 2);
+        assert_eq!(
+            *executor.statements.borrow(),
+            [
+                "BEGIN",
+                "INSERT INTO roles (name) VALUES ('admin')",
+                "UPDATE users SET role = 'admin' WHERE id = 1",
+                "COMMIT",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_split_statements_drops_empty_statements() {
+        assert_eq!(
+            split_statements("DELETE FROM a;; \n;\n-- comment only;DELETE FROM b"),
+            ["DELETE FROM a", "DELETE FROM b"]
+        );
+        assert!(split_statements(" ;\n").is_empty());
+    }
+
+    #[test]
+    fn test_files_are_ordered_by_numeric_prefix() {
+        let mut names = vec!["orders.json", "10_items.json", "2_roles.sql", "1_users.json"];
+        names.sort_by_cached_key(|name| order_key(Path::new(name)));
+
+        assert_eq!(names, ["1_users.json", "2_roles.sql", "10_items.json", "orders.json"]);
+        assert_eq!(order_key(Path::new("10_items")).1, "items");
+    }
 }
@@ -0,0 +1,158 @@
+use serde::Serialize;
+
+use super::SeedFile;
+
+/// Rows inserted from a single seed file.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct FileReport {
+    pub table: String,
+    pub rows: usize,
+}
+
+/// Outcome of a seeding run, one entry per processed file.
+#[derive(Serialize, Debug, Default, PartialEq)]
+pub struct SeedReport {
+    pub files: Vec<FileReport>,
+}
+
+impl SeedReport {
+    pub fn total_rows(&self) -> usize {
+        self.files.iter().map(|file| file.rows).sum()
+    }
+}
+
+/// Hooks called while seeding, to report progress on long runs.
+///
+/// Every method defaults to doing nothing.
+pub trait SeedObserver {
+    fn on_file_start(&mut self, _file: &SeedFile) {}
+
+    fn on_rows_inserted(&mut self, _count: usize) {}
+
+    fn on_file_done(&mut self, _report: &FileReport) {}
+
+    /// The file failed and its transaction was rolled back, so the rows reported since
+    /// [`on_file_start`](Self::on_file_start) were not stored.
+    fn on_file_failed(&mut self, _file: &SeedFile) {}
+}
+
+/// Observer logging a progress event each time another `interval` rows are inserted.
+#[derive(Debug)]
+pub struct LogProgress {
+    interval: usize,
+    table: String,
+    inserted: usize,
+    /// Rows reported for the current file, discounted if it is rolled back.
+    file_inserted: usize,
+    logged_at: usize,
+}
+
+impl LogProgress {
+    pub fn new(interval: usize) -> Self {
+        Self {
+            interval: interval.max(1),
+            table: String::new(),
+            inserted: 0,
+            file_inserted: 0,
+            logged_at: 0,
+        }
+    }
+}
+
+impl SeedObserver for LogProgress {
+    fn on_file_start(&mut self, file: &SeedFile) {
+        self.table.clone_from(&file.table);
+        self.file_inserted = 0;
+        tracing::info!(table = %file.table, rows = file.rows.len(), "Seeding table");
+    }
+
+    fn on_rows_inserted(&mut self, count: usize) {
+        self.inserted += count;
+        self.file_inserted += count;
+        if self.inserted - self.logged_at >= self.interval {
+            self.logged_at = self.inserted;
+            tracing::info!(table = %self.table, rows_inserted = self.inserted, "Seeding progress");
+        }
+    }
+
+    fn on_file_done(&mut self, report: &FileReport) {
+        tracing::info!(table = %report.table, rows = report.rows, "Seeded table");
+    }
+
+    fn on_file_failed(&mut self, file: &SeedFile) {
+        self.inserted -= self.file_inserted;
+        self.logged_at = self.logged_at.min(self.inserted);
+        if self.file_inserted > 0 {
+            tracing::warn!(
+                table = %file.table,
+                rows_rolled_back = self.file_inserted,
+                rows_inserted = self.inserted,
+                "Seed rows rolled back"
+            );
+        }
+        self.file_inserted = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::testing::CapturedLogs;
+
+    #[test]
+    fn test_progress_is_logged_every_interval() {
+        let logs = CapturedLogs::default();
+        let _guard = logs.set_default();
+
+        let mut progress = LogProgress::new(100);
+        for count in [60, 60, 60, 60] {
+            progress.on_rows_inserted(count);
+        }
+
+        let output = logs.contents();
+        assert_eq!(output.matches("Seeding progress").count(), 2);
+        assert!(output.contains("rows_inserted=120"));
+        assert!(output.contains("rows_inserted=240"));
+    }
+
+    #[test]
+    fn test_rolled_back_rows_are_discounted() {
+        let logs = CapturedLogs::default();
+        let _guard = logs.set_default();
+        let file = SeedFile {
+            path: "users.json".into(),
+            table: "users".to_string(),
+            rows: Vec::new(),
+            statements: Vec::new(),
+        };
+
+        let mut progress = LogProgress::new(100);
+        progress.on_file_start(&file);
+        progress.on_rows_inserted(150);
+        progress.on_file_failed(&file);
+        progress.on_file_start(&file);
+        progress.on_rows_inserted(90);
+
+        let output = logs.contents();
+        assert!(output.contains("rows_rolled_back=150 rows_inserted=0"));
+        assert_eq!(output.matches("Seeding progress").count(), 1);
+        assert_eq!(progress.inserted, 90);
+    }
+
+    #[test]
+    fn test_report_totals_rows() {
+        let report = SeedReport {
+            files: vec![
+                FileReport {
+                    table: "users".to_string(),
+                    rows: 3,
+                },
+                FileReport {
+                    table: "orders".to_string(),
+                    rows: 4,
+                },
+            ],
+        };
+        assert_eq!(report.total_rows(), 7);
+    }
+}
@@ -0,0 +1,94 @@
+use std::{collections::BTreeMap, fs, path::Path};
+
+use serde::Deserialize;
+
+/// SQL types that bound seed values are cast to, read from `_types.json`.
+///
+/// ```json
+/// { "created_at": "DATETIME", "settings": "JSON" }
+/// ```
+///
+/// Hints apply to the column in every seed file of the folder. Values are bound as
+/// `CAST(? AS <type>)`, for timestamps and other values the driver would otherwise send as plain
+/// strings.
+#[derive(Deserialize, Debug, Default)]
+#[serde(transparent)]
+pub struct ColumnTypes(BTreeMap<String, String>);
+
+impl ColumnTypes {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let raw = fs::read_to_string(path)?;
+        let types: Self = serde_json::from_str(&raw)?;
+
+        // The type is spliced into the SQL, so only allow what a type name can contain
+        for (column, sql_type) in &types.0 {
+            if !is_type_name(sql_type) {
+                return Err(format!(
+                    "invalid type {sql_type:?} for column {column} in {}",
+                    path.display()
+                )
+                .into());
+            }
+        }
+
+        Ok(types)
+    }
+
+    /// Add the hints of `other`, overriding columns declared in both.
+    pub fn merge(&mut self, other: ColumnTypes) {
+        self.0.extend(other.0);
+    }
+
+    pub fn get(&self, column: &str) -> Option<&str> {
+        self.0.get(column).map(String::as_str)
+    }
+}
+
+/// Whether `sql_type` is a type name such as `DATETIME` or `DECIMAL(10, 2)`: an identifier,
+/// optionally followed by one or two parenthesized lengths.
+fn is_type_name(sql_type: &str) -> bool {
+    let (name, params) = match sql_type.trim().split_once('(') {
+        | Some((name, rest)) => match rest.strip_suffix(')') {
+            | Some(params) => (name.trim_end(), Some(params)),
+            | None => return false,
+        },
+        | None => (sql_type.trim(), None),
+    };
+
+    let is_identifier = name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    let is_length = |param: &str| {
+        let param = param.trim();
+        !param.is_empty() && param.chars().all(|c| c.is_ascii_digit())
+    };
+
+    is_identifier
+        && params.is_none_or(|params| {
+            let mut lengths = params.split(',');
+            lengths.next().is_some_and(is_length)
+                && lengths.next().is_none_or(is_length)
+                && lengths.next().is_none()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_type_names() {
+        assert!(is_type_name("DATETIME"));
+        assert!(is_type_name("DECIMAL(10, 2)"));
+        assert!(is_type_name("unsigned"));
+        assert!(!is_type_name(""));
+        assert!(is_type_name("CHAR(36)"));
+        assert!(!is_type_name(""));
+        assert!(!is_type_name("CHAR); DROP TABLE users; --"));
+        assert!(!is_type_name("CHAR), SLEEP(10), CAST(1 AS CHAR"));
+        assert!(!is_type_name("DECIMAL(10, 2, 3)"));
+        assert!(!is_type_name("DECIMAL(10,)"));
+        assert!(!is_type_name("CHAR(36"));
+        assert!(!is_type_name("1CHAR"));
+        assert!(!is_type_name("UNSIGNED INTEGER"));
+    }
+}
@@ -0,0 +1,302 @@
+use std::{collections::BTreeMap, fmt, fs, path::Path};
+
+use hashbrown::{HashMap, HashSet};
+use serde::Deserialize;
+use serde_json::Value;
+
+use super::SeedFile;
+
+/// Seeder metadata read from `_meta.json`.
+///
+/// ```json
+/// { "foreign_keys": { "orders": { "user_id": "users.id" } } }
+/// ```
+#[derive(Deserialize, Debug, Default)]
+pub struct SeedMeta {
+    /// Table -> column -> referenced `table.column`.
+    #[serde(default)]
+    pub foreign_keys: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+impl SeedMeta {
+    pub fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let raw = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&raw)?)
+    }
+
+    /// Add the declarations of `other`, overriding columns declared in both.
+    pub fn merge(&mut self, other: SeedMeta) {
+        for (table, columns) in other.foreign_keys {
+            self.foreign_keys.entry(table).or_default().extend(columns);
+        }
+    }
+}
+
+/// A seed row referencing a parent row that is not part of the seed data.
+#[derive(Debug, PartialEq)]
+pub struct DanglingReference {
+    pub table: String,
+    pub row: usize,
+    pub column: String,
+    pub value: Value,
+    pub references: String,
+}
+
+#[derive(Debug)]
+pub struct ReferentialIntegrityError {
+    pub violations: Vec<DanglingReference>,
+}
+
+impl fmt::Display for ReferentialIntegrityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} seed row(s) reference missing parents:", self.violations.len())?;
+        for v in &self.violations {
+            write!(
+                f,
+                "\n  {}[{}].{} = {} references {}, which is not seeded",
+                v.table, v.row, v.column, v.value, v.references
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ReferentialIntegrityError {}
+
+/// Check that every foreign key declared in `meta` points at a row seeded before it in `files`.
+///
+/// `NULL` references are allowed. Numbers and strings are compared by their textual value so
+/// `1` and `"1"` refer to the same parent. References to a table seeded by a `.sql` script are
+/// not checked, since its rows are unknown until the script runs.
+pub fn validate_foreign_keys(
+    files: &[SeedFile],
+    meta: &SeedMeta,
+) -> Result<(), ReferentialIntegrityError> {
+    if meta.foreign_keys.is_empty() {
+        return Ok(());
+    }
+
+    // Values of every referenced `table.column`, filled in seeding order
+    let mut known: HashMap<&str, HashSet<String>> = HashMap::new();
+    let mut scripted: HashSet<&str> = HashSet::new();
+    let mut unchecked: HashSet<&str> = HashSet::new();
+    let mut violations = Vec::new();
+    for file in files {
+        if !file.statements.is_empty() {
+            scripted.insert(&file.table);
+            continue;
+        }
+
+        let columns = meta.foreign_keys.get(&file.table);
+        for (index, row) in file.rows.iter().enumerate() {
+            for (column, target) in columns.into_iter().flatten() {
+                let Some(value) = row.get(column).filter(|v| !v.is_null()) else {
+                    continue;
+                };
+
+                let table = target.split_once('.').map(|(table, _)| table);
+                if table.is_some_and(|table| scripted.contains(table)) {
+                    if unchecked.insert(target) {
+                        tracing::warn!(
+                            table = %file.table,
+                            column,
+                            references = %target,
+                            "Not checking references to a table seeded by a script"
+                        );
+                    }
+                    continue;
+                }
+
+                let found = key_of(value).is_some_and(|key| {
+                    known
+                        .get(target.as_str())
+                        .is_some_and(|values| values.contains(&key))
+                });
+                if !found {
+                    violations.push(DanglingReference {
+                        table: file.table.clone(),
+                        row: index,
+                        column: column.clone(),
+                        value: value.clone(),
+                        references: target.clone(),
+                    });
+                }
+            }
+
+            // Later rows, including those of this file, may reference this one
+            for target in meta
+                .foreign_keys
+                .values()
+                .flat_map(|columns| columns.values())
+            {
+                let Some((_, column)) = target
+                    .split_once('.')
+                    .filter(|(table, _)| *table == file.table)
+                else {
+                    continue;
+                };
+                if let Some(key) = row.get(column).and_then(key_of) {
+                    known.entry(target).or_default().insert(key);
+                }
+            }
+        }
+    }
+
+    match violations.is_empty() {
+        | true => Ok(()),
+        | false => Err(ReferentialIntegrityError {
+            violations,
+        }),
+    }
+}
+
+fn key_of(value: &Value) -> Option<String> {
+    match value {
+        | Value::String(s) => Some(s.clone()),
+        | Value::Number(n) => Some(n.to_string()),
+        | Value::Bool(b) => Some(b.to_string()),
+        | _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn file(table: &str, rows: Value) -> SeedFile {
+        SeedFile {
+            path: format!("{table}.json").into(),
+            table: table.to_string(),
+            rows: serde_json::from_value(rows).unwrap(),
+            statements: Vec::new(),
+        }
+    }
+
+    fn meta() -> SeedMeta {
+        serde_json::from_value(json!({
+            "foreign_keys": { "orders": { "user_id": "users.id" } }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_valid_references_pass() {
+        let files = vec![
+            file("users", json!([{ "id": 1 }, { "id": "2" }])),
+            file(
+                "orders",
+                json!([
+                    { "id": 10, "user_id": 1 },
+                    { "id": 11, "user_id": 2 },
+                    { "id": 12, "user_id": null },
+                ]),
+            ),
+        ];
+
+        assert!(validate_foreign_keys(&files, &meta()).is_ok());
+    }
+
+    #[test]
+    fn test_dangling_reference_is_reported() {
+        let files = vec![
+            file("users", json!([{ "id": 1 }])),
+            file(
+                "orders",
+                json!([
+                    { "id": 10, "user_id": 1 },
+                    { "id": 11, "user_id": 3 },
+                ]),
+            ),
+        ];
+
+        let err = validate_foreign_keys(&files, &meta()).unwrap_err();
+        assert_eq!(
+            err.violations,
+            vec![DanglingReference {
+                table: "orders".to_string(),
+                row: 1,
+                column: "user_id".to_string(),
+                value: json!(3),
+                references: "users.id".to_string(),
+            }]
+        );
+        assert!(
+            err.to_string()
+                .contains("orders[1].user_id = 3 references users.id")
+        );
+    }
+
+    #[test]
+    fn test_missing_parent_table_is_reported() {
+        let files = vec![file("orders", json!([{ "id": 10, "user_id": 1 }]))];
+
+        let err = validate_foreign_keys(&files, &meta()).unwrap_err();
+        assert_eq!(err.violations.len(), 1);
+    }
+
+    #[test]
+    fn test_parent_seeded_after_child_is_reported() {
+        let files = vec![
+            file("orders", json!([{ "id": 10, "user_id": 1 }])),
+            file("users", json!([{ "id": 1 }])),
+        ];
+
+        let err = validate_foreign_keys(&files, &meta()).unwrap_err();
+        assert_eq!(err.violations.len(), 1);
+        assert_eq!(err.violations[0].table, "orders");
+    }
+
+    #[test]
+    fn test_self_references_only_see_earlier_rows() {
+        let meta = serde_json::from_value(json!({
+            "foreign_keys": { "categories": { "parent_id": "categories.id" } }
+        }))
+        .unwrap();
+        let files = vec![file(
+            "categories",
+            json!([
+                { "id": 1, "parent_id": null },
+                { "id": 2, "parent_id": 1 },
+                { "id": 3, "parent_id": 4 },
+                { "id": 4, "parent_id": null },
+            ]),
+        )];
+
+        let err = validate_foreign_keys(&files, &meta).unwrap_err();
+        assert_eq!(err.violations.len(), 1);
+        assert_eq!(err.violations[0].row, 2);
+    }
+
+    #[test]
+    fn test_references_to_scripted_tables_are_not_checked() {
+        let mut users = file("users", json!([]));
+        users.statements = vec!["INSERT INTO users (id) VALUES (1)".to_string()];
+        let files = vec![users, file("orders", json!([{ "id": 10, "user_id": 1 }]))];
+
+        assert!(validate_foreign_keys(&files, &meta()).is_ok());
+    }
+
+    #[test]
+    fn test_no_meta_skips_validation() {
+        let files = vec![file("orders", json!([{ "id": 10, "user_id": 1 }]))];
+
+        assert!(validate_foreign_keys(&files, &SeedMeta::default()).is_ok());
+    }
+
+    #[test]
+    fn test_merge_overrides_columns() {
+        let mut meta = meta();
+        meta.merge(
+            serde_json::from_value(json!({
+                "foreign_keys": { "orders": { "user_id": "customers.id", "sku": "products.sku" } }
+            }))
+            .unwrap(),
+        );
+
+        let orders = &meta.foreign_keys["orders"];
+        assert_eq!(orders["user_id"], "customers.id");
+        assert_eq!(orders["sku"], "products.sku");
+    }
+}
@@ -0,0 +1,270 @@
+use std::{
+    fmt,
+    future::Future,
+    sync::{
+        Arc,
+        atomic::{AtomicBool, Ordering},
+    },
+    time::{Duration, Instant},
+};
+
+use futures_util::future::{LocalBoxFuture, join_all};
+
+/// Shared flag telling `/health/ready` whether startup has completed.
+#[derive(Clone, Debug, Default)]
+pub struct Readiness(Arc<AtomicBool>);
+
+impl Readiness {
+    pub fn is_ready(&self) -> bool {
+        self.0.load(Ordering::Acquire)
+    }
+
+    pub fn mark_ready(&self) {
+        self.0.store(true, Ordering::Release);
+    }
+}
+
+#[derive(Debug)]
+pub enum StartupError<E> {
+    /// A startup step failed.
+    Failed(E),
+
+    /// Startup did not complete before the deadline.
+    DeadlineExceeded(Duration),
+}
+
+impl<E: fmt::Display> fmt::Display for StartupError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            | StartupError::Failed(e) => write!(f, "startup failed: {e}"),
+            | StartupError::DeadlineExceeded(deadline) => {
+                write!(f, "startup did not complete within {}s", deadline.as_secs())
+            }
+        }
+    }
+}
+
+/// Run the startup steps and flip `readiness` once they all succeeded within `deadline`.
+///
+/// Readiness is left untouched on failure or timeout so the service keeps reporting not-ready.
+pub async fn complete_startup<F, E>(
+    readiness: &Readiness,
+    deadline: Duration,
+    steps: F,
+) -> Result<(), StartupError<E>>
+where
+    F: Future<Output = Result<(), E>>,
+{
+    match actix_rt::time::timeout(deadline, steps).await {
+        | Ok(Ok(())) => {
+            readiness.mark_ready();
+            tracing::info!("Startup completed, service is ready");
+            Ok(())
+        }
+        | Ok(Err(e)) => Err(StartupError::Failed(e)),
+        | Err(_) => Err(StartupError::DeadlineExceeded(deadline)),
+    }
+}
+
+/// An external service startup waits for, checked by running `probe` until it succeeds.
+pub struct Dependency<'a> {
+    pub name: &'static str,
+    pub timeout: Duration,
+    probe: Box<dyn Fn() -> LocalBoxFuture<'a, Result<(), String>> + 'a>,
+}
+
+impl<'a> Dependency<'a> {
+    pub fn new<F, Fut, E>(name: &'static str, timeout: Duration, probe: F) -> Self
+    where
+        F: Fn() -> Fut + 'a,
+        Fut: Future<Output = Result<(), E>> + 'a,
+        E: fmt::Display,
+    {
+        Self {
+            name,
+            timeout,
+            probe: Box::new(move || {
+                let attempt = probe();
+                Box::pin(async move { attempt.await.map_err(|e| e.to_string()) })
+            }),
+        }
+    }
+}
+
+/// Dependencies that didn't respond before their timeout, with the last error of each.
+#[derive(Debug)]
+pub struct DependencyWaitError {
+    pub unavailable: Vec<(&'static str, String)>,
+}
+
+impl fmt::Display for DependencyWaitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "dependencies never came up:")?;
+        for (name, error) in &self.unavailable {
+            write!(f, " {name} ({error})")?;
+        }
+        Ok(())
+    }
+}
+
+/// Poll all `dependencies` concurrently every `interval` until each has responded.
+///
+/// Meant for local development, where the service is often started before the containers it
+/// depends on. Fails with a summary once any dependency hasn't come up within its timeout.
+pub async fn wait_for(
+    dependencies: Vec<Dependency<'_>>,
+    interval: Duration,
+) -> Result<(), DependencyWaitError> {
+    let results = join_all(dependencies.iter().map(|dependency| async move {
+        wait_for_one(dependency, interval)
+            .await
+            .map_err(|e| (dependency.name, e))
+    }))
+    .await;
+
+    let unavailable: Vec<_> = results.into_iter().filter_map(Result::err).collect();
+    match unavailable.is_empty() {
+        | true => Ok(()),
+        | false => Err(DependencyWaitError {
+            unavailable,
+        }),
+    }
+}
+
+async fn wait_for_one(dependency: &Dependency<'_>, interval: Duration) -> Result<(), String> {
+    let started = Instant::now();
+    loop {
+        let remaining = dependency.timeout.saturating_sub(started.elapsed());
+        let error = match actix_rt::time::timeout(remaining, (dependency.probe)()).await {
+            | Ok(Ok(())) => {
+                tracing::info!(dependency = dependency.name, "Dependency is available");
+                return Ok(());
+            }
+            | Ok(Err(e)) => e,
+            | Err(_) => "timed out".to_string(),
+        };
+
+        let remaining = dependency.timeout.saturating_sub(started.elapsed());
+        if remaining.is_zero() {
+            return Err(error);
+        }
+
+        tracing::info!(
+            dependency = dependency.name,
+            remaining_secs = remaining.as_secs(),
+            error = %error,
+            "Waiting for dependency"
+        );
+        actix_rt::time::sleep(interval.min(remaining)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use sqlx::{Connection, MySqlConnection};
+
+    use super::*;
+
+    async fn slow_seed(delay: Duration) -> Result<(), String> {
+        actix_rt::time::sleep(delay).await;
+        Ok(())
+    }
+
+    #[actix_rt::test]
+    async fn test_ready_only_after_startup_completes() {
+        let readiness = Readiness::default();
+
+        let startup = {
+            let readiness = readiness.clone();
+            actix_rt::spawn(async move {
+                complete_startup(
+                    &readiness,
+                    Duration::from_secs(5),
+                    slow_seed(Duration::from_millis(50)),
+                )
+                .await
+            })
+        };
+
+        actix_rt::time::sleep(Duration::from_millis(10)).await;
+        assert!(!readiness.is_ready());
+
+        startup.await.unwrap().unwrap();
+        assert!(readiness.is_ready());
+    }
+
+    #[actix_rt::test]
+    async fn test_deadline_exceeded_stays_not_ready() {
+        let readiness = Readiness::default();
+
+        let result = complete_startup(
+            &readiness,
+            Duration::from_millis(10),
+            slow_seed(Duration::from_secs(5)),
+        )
+        .await;
+
+        assert!(matches!(result, Err(StartupError::DeadlineExceeded(_))));
+        assert!(!readiness.is_ready());
+    }
+
+    #[actix_rt::test]
+    async fn test_failed_step_stays_not_ready() {
+        let readiness = Readiness::default();
+
+        let result = complete_startup(&readiness, Duration::from_secs(5), async {
+            Err("migrations failed")
+        })
+        .await;
+
+        assert!(matches!(result, Err(StartupError::Failed("migrations failed"))));
+        assert!(!readiness.is_ready());
+    }
+
+    #[actix_rt::test]
+    async fn test_wait_for_succeeds_once_dependency_responds() {
+        let attempts = Cell::new(0);
+        let dependency = Dependency::new("flaky", Duration::from_secs(5), || {
+            attempts.set(attempts.get() + 1);
+            let attempt = attempts.get();
+            async move {
+                match attempt {
+                    | 1..=2 => Err("connection refused"),
+                    | _ => Ok(()),
+                }
+            }
+        });
+
+        wait_for(vec![dependency], Duration::from_millis(1))
+            .await
+            .unwrap();
+        assert_eq!(attempts.get(), 3);
+    }
+
+    #[actix_rt::test]
+    async fn test_wait_for_reports_unreachable_dependencies() {
+        let database = Dependency::new("database", Duration::from_millis(200), || async {
+            MySqlConnection::connect("mysql://root@127.0.0.1:1/template_service")
+                .await?
+                .close()
+                .await
+        });
+        let cache =
+            Dependency::new("cache", Duration::from_millis(200), || async { Ok::<_, String>(()) });
+
+        let started = Instant::now();
+        let err = wait_for(vec![database, cache], Duration::from_millis(20))
+            .await
+            .unwrap_err();
+
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert_eq!(err.unavailable.len(), 1);
+        assert_eq!(err.unavailable[0].0, "database");
+        assert!(
+            err.to_string()
+                .starts_with("dependencies never came up: database (")
+        );
+    }
+}
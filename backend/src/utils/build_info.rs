@@ -0,0 +1,53 @@
+use serde::Serialize;
+
+/// Value reported for build metadata that wasn't available at compile time.
+const UNKNOWN: &str = "unknown";
+
+/// Metadata about the running build, embedded at compile time by `build.rs`.
+#[derive(Serialize, Debug, Clone, Copy)]
+pub struct BuildInfo {
+    /// Crate version from `Cargo.toml`.
+    pub version: &'static str,
+
+    /// Short git commit SHA, or `"unknown"` when built outside a git checkout.
+    pub git_sha: &'static str,
+
+    /// RFC 3339 timestamp of the build.
+    pub build_timestamp: &'static str,
+
+    /// Version of the compiler used for the build.
+    pub rustc_version: &'static str,
+}
+
+pub const BUILD_INFO: BuildInfo = BuildInfo {
+    version: env!("CARGO_PKG_VERSION"),
+    git_sha: or_unknown(option_env!("BUILD_GIT_SHA")),
+    build_timestamp: or_unknown(option_env!("BUILD_TIMESTAMP")),
+    rustc_version: or_unknown(option_env!("BUILD_RUSTC_VERSION")),
+};
+
+const fn or_unknown(value: Option<&'static str>) -> &'static str {
+    match value {
+        | Some(value) => value,
+        | None => UNKNOWN,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_values_fall_back_to_unknown() {
+        assert_eq!(or_unknown(None), "unknown");
+        assert_eq!(or_unknown(Some("abc123")), "abc123");
+    }
+
+    #[test]
+    fn test_build_info_is_populated() {
+        assert_eq!(BUILD_INFO.version, env!("CARGO_PKG_VERSION"));
+        assert!(!BUILD_INFO.git_sha.is_empty());
+        assert!(!BUILD_INFO.build_timestamp.is_empty());
+        assert!(!BUILD_INFO.rustc_version.is_empty());
+    }
+}
@@ -0,0 +1,303 @@
+use std::{
+    fmt::{self, Display},
+    future::Future,
+    time::Duration,
+};
+
+use sqlx::{MySql, MySqlPool, Transaction, mysql::MySqlPoolOptions};
+
+use crate::config::DatabaseConfig;
+
+/// Upper bound for the delay between two connection attempts.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(30);
+
+/// Delay before the first retry of a failed transaction, doubled after every further failure.
+const TXN_RETRY_DELAY: Duration = Duration::from_millis(50);
+
+/// SQLSTATE of failures resolved by running the transaction again. MySQL reports deadlocks
+/// (error 1213) and serialization failures under it.
+const RETRYABLE_SQLSTATE: &str = "40001";
+
+tokio::task_local! {
+    /// Set while a [`with_txn`] closure runs, to reject nested transactions.
+    static IN_TRANSACTION: ();
+}
+
+#[derive(Debug)]
+pub enum TransactionError {
+    /// [`with_txn`] was called from inside another `with_txn` closure.
+    Nested,
+    Database(sqlx::Error),
+}
+
+impl fmt::Display for TransactionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            | TransactionError::Nested => write!(
+                f,
+                "with_txn called inside another transaction, pass the outer transaction instead"
+            ),
+            | TransactionError::Database(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl std::error::Error for TransactionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            | TransactionError::Nested => None,
+            | TransactionError::Database(e) => Some(e),
+        }
+    }
+}
+
+/// Run `f` in a transaction, committing when it returns `Ok` and rolling back otherwise.
+///
+/// When the transaction fails on a deadlock or serialization conflict the whole closure is run
+/// again in a fresh transaction, up to `max_attempts` times in total (usually
+/// `database.txn_max_attempts`), so `f` must not have side effects outside the database. Calling
+/// `with_txn` from inside `f` fails with [`TransactionError::Nested`].
+#[cfg_attr(
+    not(test),
+    expect(dead_code, reason = "handlers call it once they do multi-statement writes")
+)]
+pub async fn with_txn<T, F>(
+    pool: &MySqlPool,
+    max_attempts: u32,
+    mut f: F,
+) -> Result<T, TransactionError>
+where
+    F: AsyncFnMut(&mut Transaction<'static, MySql>) -> Result<T, sqlx::Error>,
+{
+    if IN_TRANSACTION.try_with(|_| ()).is_ok() {
+        return Err(TransactionError::Nested);
+    }
+
+    let attempt = async || {
+        let mut txn = pool.begin().await?;
+        match f(&mut txn).await {
+            | Ok(value) => {
+                txn.commit().await?;
+                Ok(value)
+            }
+            | Err(e) => {
+                if let Err(rollback_error) = txn.rollback().await {
+                    tracing::warn!(error = %rollback_error, "Transaction rollback failed");
+                }
+                Err(e)
+            }
+        }
+    };
+
+    IN_TRANSACTION
+        .scope((), retry_transaction(max_attempts, TXN_RETRY_DELAY, attempt))
+        .await
+        .map_err(TransactionError::Database)
+}
+
+/// Run `attempt` until it succeeds or fails with a non-retryable error, at most `max_attempts`
+/// times, backing off exponentially from `base_delay`.
+async fn retry_transaction<T>(
+    max_attempts: u32,
+    base_delay: Duration,
+    mut attempt: impl AsyncFnMut() -> Result<T, sqlx::Error>,
+) -> Result<T, sqlx::Error> {
+    let mut attempts: u32 = 0;
+    loop {
+        attempts += 1;
+        match attempt().await {
+            | Ok(value) => return Ok(value),
+            | Err(e) if attempts >= max_attempts || !is_retryable(&e) => return Err(e),
+            | Err(e) => {
+                let delay = backoff(base_delay, attempts);
+                tracing::warn!(
+                    attempt = attempts,
+                    max_attempts,
+                    retry_in_ms = delay.as_millis() as u64,
+                    error = %e,
+                    "Transaction conflict, retrying"
+                );
+                actix_rt::time::sleep(delay).await;
+            }
+        }
+    }
+}
+
+fn is_retryable(e: &sqlx::Error) -> bool {
+    matches!(
+        e,
+        sqlx::Error::Database(db_err)
+            if db_err.code().is_some_and(|code| code == RETRYABLE_SQLSTATE)
+    )
+}
+
+/// Run `connect` until it succeeds, retrying up to `retries` times with exponential backoff.
+///
+/// The delay starts at `base_delay` and doubles after every failed attempt, capped at
+/// [`MAX_RETRY_DELAY`]. The last error is returned once all attempts are exhausted.
+pub async fn connect_with_retry<F, Fut, T, E>(
+    retries: u32,
+    base_delay: Duration,
+    mut connect: F,
+) -> Result<T, E>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, E>>,
+    E: Display,
+{
+    let mut attempt: u32 = 0;
+    loop {
+        attempt += 1;
+        match connect().await {
+            | Ok(value) => return Ok(value),
+            | Err(e) if attempt > retries => return Err(e),
+            | Err(e) => {
+                let delay = backoff(base_delay, attempt);
+                tracing::warn!(
+                    attempt,
+                    max_attempts = retries + 1,
+                    retry_in_ms = delay.as_millis() as u64,
+                    error = %e,
+                    "Database connection failed, retrying"
+                );
+                actix_rt::time::sleep(delay).await;
+            }
+        }
+    }
+}
+
+/// Delay after the `attempt`th failure: `base_delay` doubled per earlier failure, capped at
+/// [`MAX_RETRY_DELAY`].
+fn backoff(base_delay: Duration, attempt: u32) -> Duration {
+    base_delay
+        .saturating_mul(2u32.saturating_pow(attempt.saturating_sub(1)))
+        .min(MAX_RETRY_DELAY)
+}
+
+/// Build the read replica pool if `DATABASE_REPLICA_URL` is configured.
+///
+/// The pool connects lazily, so an unreachable replica doesn't hold up startup.
+pub fn replica_pool(config: &DatabaseConfig) -> Result<Option<MySqlPool>, sqlx::Error> {
+    let Some(url) = &config.replica_url else {
+        return Ok(None);
+    };
+
+    let pool = MySqlPoolOptions::new()
+        .max_connections(config.max_connections)
+        .connect_lazy(url)?;
+    Ok(Some(pool))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::{state::AppState, utils::testing::database_error};
+
+    #[actix_rt::test]
+    async fn test_succeeds_after_failures() {
+        let attempts = Cell::new(0);
+        let result = connect_with_retry(5, Duration::ZERO, || {
+            attempts.set(attempts.get() + 1);
+            let attempt = attempts.get();
+            async move {
+                match attempt {
+                    | 1..=3 => Err("connection refused"),
+                    | _ => Ok("connected"),
+                }
+            }
+        })
+        .await;
+
+        assert_eq!(result, Ok("connected"));
+        assert_eq!(attempts.get(), 4);
+    }
+
+    #[actix_rt::test]
+    async fn test_gives_up_after_retries() {
+        let attempts = Cell::new(0);
+        let result: Result<(), _> = connect_with_retry(2, Duration::ZERO, || {
+            attempts.set(attempts.get() + 1);
+            async { Err("connection refused") }
+        })
+        .await;
+
+        assert_eq!(result, Err("connection refused"));
+        assert_eq!(attempts.get(), 3);
+    }
+
+    #[actix_rt::test]
+    async fn test_zero_retries_tries_once() {
+        let attempts = Cell::new(0);
+        let result: Result<(), _> = connect_with_retry(0, Duration::ZERO, || {
+            attempts.set(attempts.get() + 1);
+            async { Err("connection refused") }
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts.get(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn test_transaction_retries_conflicts() {
+        let attempts = Cell::new(0);
+        let result = retry_transaction(3, Duration::ZERO, async || {
+            attempts.set(attempts.get() + 1);
+            match attempts.get() {
+                | 1 | 2 => Err(database_error(1213, None)),
+                | _ => Ok("committed"),
+            }
+        })
+        .await;
+
+        assert_eq!(result.unwrap(), "committed");
+        assert_eq!(attempts.get(), 3);
+    }
+
+    #[actix_rt::test]
+    async fn test_transaction_gives_up_after_max_attempts() {
+        let attempts = Cell::new(0);
+        let result: Result<(), _> = retry_transaction(3, Duration::ZERO, async || {
+            attempts.set(attempts.get() + 1);
+            Err(database_error(1213, None))
+        })
+        .await;
+
+        assert!(is_retryable(&result.unwrap_err()));
+        assert_eq!(attempts.get(), 3);
+    }
+
+    #[actix_rt::test]
+    async fn test_other_errors_are_not_retried() {
+        let attempts = Cell::new(0);
+        let result: Result<(), _> = retry_transaction(3, Duration::ZERO, async || {
+            attempts.set(attempts.get() + 1);
+            Err(database_error(1062, None))
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts.get(), 1);
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_the_cap() {
+        let base = Duration::from_millis(50);
+        assert_eq!(backoff(base, 1), base);
+        assert_eq!(backoff(base, 3), Duration::from_millis(200));
+        assert_eq!(backoff(base, 40), MAX_RETRY_DELAY);
+    }
+
+    #[actix_rt::test]
+    async fn test_nested_transaction_is_rejected() {
+        let state = AppState::for_tests();
+
+        let result = IN_TRANSACTION
+            .scope((), with_txn(&state.db, 3, async |_| Ok(())))
+            .await;
+        assert!(matches!(result, Err(TransactionError::Nested)));
+        assert_eq!(state.db.size(), 0);
+    }
+}
@@ -0,0 +1,292 @@
+use std::{fmt, io::Write};
+
+use serde_json::{Map, Value};
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+use tracing::{
+    Event,
+    Subscriber,
+    field::{Field, Visit},
+    span::{Attributes, Id, Record},
+};
+use tracing_subscriber::{Layer, fmt::MakeWriter, layer::Context, registry::LookupSpan};
+use uuid::Uuid;
+
+/// Version of the Elastic Common Schema the output follows.
+const ECS_VERSION: &str = "8.11.0";
+
+/// Tracing layer writing one [Elastic Common Schema](https://www.elastic.co/guide/en/ecs/current)
+/// JSON object per event.
+///
+/// ```json
+/// {
+///   "@timestamp": "2025-10-21T18:51:43.369115Z",
+///   "log.level": "INFO",
+///   "message": "finished processing request",
+///   "ecs.version": "8.11.0",
+///   "service.name": "template-service",
+///   "service.environment": "production",
+///   "log.logger": "backend::middleware::access_log",
+///   "trace.id": "5f1a3c8e0b9d4e7fa2c61d0b8e4f9a37",
+///   "span.id": "9c2e4b7a1d0f3e68",
+///   "labels": { "http.method": "GET", "http.status_code": 200 }
+/// }
+/// ```
+///
+/// Fields of the event and its enclosing spans go under `labels`, inner spans overriding outer
+/// ones. `trace.id` is the `request_id` of the outermost span, usually the request span, or a
+/// random id if it has none. `span.id` is a random id given to the innermost span when it opens.
+pub struct EcsLayer<W> {
+    service_name: String,
+    environment: String,
+    make_writer: W,
+}
+
+impl<W> EcsLayer<W> {
+    pub fn new(service_name: &str, environment: &str, make_writer: W) -> Self {
+        Self {
+            service_name: service_name.to_string(),
+            environment: environment.to_string(),
+            make_writer,
+        }
+    }
+}
+
+/// Fields recorded on a span, kept in its extensions.
+struct SpanFields(Map<String, Value>);
+
+/// Trace and span id of a span, kept in its extensions.
+///
+/// The registry's own span ids are slab indices reused once a span closes, so they can't tell
+/// requests apart.
+#[derive(Clone, Copy)]
+struct SpanIds {
+    trace: u128,
+    span: u64,
+}
+
+struct JsonVisitor<'a>(&'a mut Map<String, Value>);
+
+impl Visit for JsonVisitor<'_> {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.into());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{value:?}").into());
+    }
+}
+
+impl<S, W> Layer<S> for EcsLayer<W>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'a> MakeWriter<'a> + 'static,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+
+        let mut fields = Map::new();
+        attrs.record(&mut JsonVisitor(&mut fields));
+        let trace = match span.parent() {
+            | Some(parent) => parent.extensions().get::<SpanIds>().map(|ids| ids.trace),
+            | None => fields
+                .get("request_id")
+                .and_then(Value::as_str)
+                .and_then(|request_id| Uuid::parse_str(request_id).ok())
+                .map(|request_id| request_id.as_u128()),
+        };
+
+        let mut extensions = span.extensions_mut();
+        extensions.insert(SpanFields(fields));
+        extensions.insert(SpanIds {
+            trace: trace.unwrap_or_else(rand::random),
+            span: rand::random(),
+        });
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else {
+            return;
+        };
+
+        if let Some(fields) = span.extensions_mut().get_mut::<SpanFields>() {
+            values.record(&mut JsonVisitor(&mut fields.0));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut labels = Map::new();
+        let mut ids = None;
+        if let Some(scope) = ctx.event_scope(event) {
+            for span in scope.from_root() {
+                let extensions = span.extensions();
+                if let Some(fields) = extensions.get::<SpanFields>() {
+                    labels.extend(fields.0.clone());
+                }
+                ids = extensions.get::<SpanIds>().copied();
+            }
+        }
+
+        let mut fields = Map::new();
+        event.record(&mut JsonVisitor(&mut fields));
+        let message = fields.remove("message").unwrap_or_default();
+        labels.extend(fields);
+
+        let metadata = event.metadata();
+        let timestamp = OffsetDateTime::now_utc()
+            .format(&Rfc3339)
+            .unwrap_or_default();
+
+        let mut line = Map::new();
+        line.insert("@timestamp".to_string(), timestamp.into());
+        line.insert("log.level".to_string(), metadata.level().as_str().into());
+        line.insert("message".to_string(), message);
+        line.insert("ecs.version".to_string(), ECS_VERSION.into());
+        line.insert("service.name".to_string(), self.service_name.clone().into());
+        line.insert("service.environment".to_string(), self.environment.clone().into());
+        line.insert("log.logger".to_string(), metadata.target().into());
+        if let Some(ids) = ids {
+            line.insert("trace.id".to_string(), format!("{:032x}", ids.trace).into());
+            line.insert("span.id".to_string(), format!("{:016x}", ids.span).into());
+        }
+        if !labels.is_empty() {
+            line.insert("labels".to_string(), Value::Object(labels));
+        }
+
+        let mut output = Value::Object(line).to_string();
+        output.push('\n');
+        let _ = self.make_writer.make_writer().write_all(output.as_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use tracing_subscriber::{Registry, layer::SubscriberExt};
+
+    use super::*;
+    use crate::utils::testing::CapturedLogs;
+
+    fn capture(emit: impl FnOnce()) -> Vec<Value> {
+        let logs = CapturedLogs::default();
+        let subscriber =
+            Registry::default().with(EcsLayer::new("template-service", "test", logs.clone()));
+        tracing::subscriber::with_default(subscriber, emit);
+
+        logs.contents()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect()
+    }
+
+    fn keys(line: &Value) -> Vec<&str> {
+        let mut keys: Vec<&str> = line
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        keys.sort();
+        keys
+    }
+
+    #[test]
+    fn test_event_fields() {
+        let lines = capture(|| tracing::warn!(user = "ada", attempts = 3, "Login failed"));
+
+        let line = &lines[0];
+        assert_eq!(
+            keys(line),
+            [
+                "@timestamp",
+                "ecs.version",
+                "labels",
+                "log.level",
+                "log.logger",
+                "message",
+                "service.environment",
+                "service.name",
+            ]
+        );
+        assert_eq!(line["log.level"], "WARN");
+        assert_eq!(line["message"], "Login failed");
+        assert_eq!(line["ecs.version"], ECS_VERSION);
+        assert_eq!(line["service.name"], "template-service");
+        assert_eq!(line["service.environment"], "test");
+        assert_eq!(line["log.logger"], "backend::utils::ecs::tests");
+        assert_eq!(line["labels"], json!({ "user": "ada", "attempts": 3 }));
+        assert!(line["@timestamp"].as_str().unwrap().ends_with('Z'));
+    }
+
+    #[test]
+    fn test_span_fields_and_ids() {
+        let lines = capture(|| {
+            let request = tracing::info_span!("request", http.method = "GET", http.route = "/");
+            let _request = request.enter();
+            let query = tracing::info_span!("query", http.route = "/version", db.rows = 1);
+            let _query = query.enter();
+            query.record("db.rows", 2);
+            tracing::info!("Query finished");
+        });
+
+        let line = &lines[0];
+        assert_eq!(
+            line["labels"],
+            json!({ "http.method": "GET", "http.route": "/version", "db.rows": 2 })
+        );
+        assert_eq!(line["trace.id"].as_str().unwrap().len(), 32);
+        assert_eq!(line["span.id"].as_str().unwrap().len(), 16);
+        assert_ne!(line["trace.id"].as_str().unwrap()[16..], *line["span.id"].as_str().unwrap());
+    }
+
+    #[test]
+    fn test_trace_id_is_the_request_id() {
+        let lines = capture(|| {
+            let request = tracing::info_span!(
+                "request",
+                request_id = %"5f1a3c8e-0b9d-4e7f-a2c6-1d0b8e4f9a37",
+            );
+            let _request = request.enter();
+            tracing::info!("Outer");
+            let query = tracing::info_span!("query");
+            let _query = query.enter();
+            tracing::info!("Inner");
+        });
+
+        assert_eq!(lines[0]["trace.id"], "5f1a3c8e0b9d4e7fa2c61d0b8e4f9a37");
+        assert_eq!(lines[1]["trace.id"], lines[0]["trace.id"]);
+        assert_ne!(lines[1]["span.id"], lines[0]["span.id"]);
+    }
+
+    #[test]
+    fn test_sequential_requests_get_distinct_ids() {
+        let lines = capture(|| {
+            for _ in 0..2 {
+                let request = tracing::info_span!("request");
+                let _request = request.enter();
+                tracing::info!("Handled");
+            }
+        });
+
+        assert_ne!(lines[0]["trace.id"], lines[1]["trace.id"]);
+        assert_ne!(lines[0]["span.id"], lines[1]["span.id"]);
+    }
+}
@@ -0,0 +1,252 @@
+use std::{
+    backtrace::{Backtrace, BacktraceStatus},
+    fmt,
+    future::Future,
+    panic::PanicHookInfo,
+};
+
+use tracing::{
+    Span,
+    Subscriber,
+    field::{Field, Visit},
+    span::{Attributes, Id},
+};
+use tracing_subscriber::{
+    Layer,
+    Registry,
+    layer::Context,
+    registry::{LookupSpan, SpanRef},
+};
+
+use crate::config::LoggingConfig;
+
+/// Keeps error reporting alive for the lifetime of the process.
+///
+/// Hold on to the value returned by [`init_error_reporting`] until shutdown; dropping it flushes
+/// any pending Sentry events when the `sentry` feature is enabled.
+pub struct ErrorReporting {
+    #[cfg(feature = "sentry")]
+    _sentry: Option<sentry::ClientInitGuard>,
+}
+
+/// Install the structured panic hook and, with the `sentry` feature, the Sentry client.
+///
+/// Sentry is only initialized when `SENTRY_DSN` is set. Without the feature this only installs
+/// the panic hook.
+pub fn init_error_reporting(config: &LoggingConfig) -> ErrorReporting {
+    install_panic_hook();
+
+    #[cfg(feature = "sentry")]
+    {
+        ErrorReporting {
+            _sentry: init_sentry(config),
+        }
+    }
+
+    #[cfg(not(feature = "sentry"))]
+    {
+        if config.sentry_dsn.is_some() {
+            tracing::warn!(
+                "SENTRY_DSN is set but the service was built without the `sentry` feature"
+            );
+        }
+        ErrorReporting {}
+    }
+}
+
+#[cfg(feature = "sentry")]
+fn init_sentry(config: &LoggingConfig) -> Option<sentry::ClientInitGuard> {
+    let dsn = config.sentry_dsn.as_deref()?;
+
+    let guard = sentry::init(sentry::ClientOptions {
+        dsn: match dsn.parse() {
+            | Ok(dsn) => Some(dsn),
+            | Err(e) => {
+                tracing::error!(error = %e, "Invalid SENTRY_DSN, Sentry reporting disabled");
+                return None;
+            }
+        },
+        release: sentry::release_name!(),
+        environment: Some(config.environment.clone().into()),
+        ..Default::default()
+    });
+    sentry::configure_scope(|scope| scope.set_tag("service", &config.service_name));
+
+    tracing::info!("Sentry error reporting enabled");
+    Some(guard)
+}
+
+/// Report a handled error, such as an internal error answered with a `500`, to Sentry.
+///
+/// The event carries the service name and environment set at initialization and is tagged with
+/// `request_id` when given. Compiles to a no-op without the `sentry` feature.
+pub fn capture_message(message: &str, request_id: Option<&str>) {
+    #[cfg(feature = "sentry")]
+    sentry::with_scope(
+        |scope| {
+            if let Some(request_id) = request_id {
+                scope.set_tag("request_id", request_id);
+            }
+        },
+        || sentry::capture_message(message, sentry::Level::Error),
+    );
+
+    #[cfg(not(feature = "sentry"))]
+    let _ = (message, request_id);
+}
+
+/// Spawn `future` on the current runtime, logging and reporting it if it panics.
+///
+/// A panic otherwise only ends the task, which nothing awaits.
+pub fn spawn_reported<F>(name: &'static str, future: F)
+where
+    F: Future<Output = ()> + 'static,
+{
+    let task = actix_rt::spawn(future);
+    actix_rt::spawn(async move {
+        if let Err(e) = task.await {
+            tracing::error!(task = name, error = %e, "Background task failed");
+            capture_message(&format!("Background task {name} failed: {e}"), None);
+        }
+    });
+}
+
+/// Layer keeping the `request_id` field of spans, such as the request span of
+/// `tracing_actix_web`, for [`current_request_id`].
+pub struct RequestIdLayer;
+
+/// `request_id` recorded on a span, kept in its extensions.
+struct RequestId(String);
+
+struct RequestIdVisitor(Option<String>);
+
+impl Visit for RequestIdVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "request_id" {
+            self.0 = Some(value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "request_id" {
+            self.0 = Some(format!("{value:?}"));
+        }
+    }
+}
+
+impl<S> Layer<S> for RequestIdLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let mut visitor = RequestIdVisitor(None);
+        attrs.record(&mut visitor);
+        if let (Some(request_id), Some(span)) = (visitor.0, ctx.span(id)) {
+            span.extensions_mut().insert(RequestId(request_id));
+        }
+    }
+}
+
+/// `request_id` of the innermost enclosing span that has one.
+pub fn current_request_id() -> Option<String> {
+    let id = Span::current().id()?;
+    tracing::dispatcher::get_default(|dispatch| {
+        let registry = dispatch.downcast_ref::<Registry>()?;
+        let span: SpanRef<'_, Registry> = registry.span(&id)?;
+        span.scope()
+            .find_map(|span| span.extensions().get::<RequestId>().map(|id| id.0.clone()))
+    })
+}
+
+/// Log panics as structured tracing events before handing them to the previous hook.
+///
+/// The backtrace is only captured when enabled through `RUST_BACKTRACE`.
+fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        log_panic(info);
+        previous(info);
+    }));
+}
+
+fn log_panic(info: &PanicHookInfo<'_>) {
+    let message = match info.payload().downcast_ref::<&str>() {
+        | Some(message) => message.to_string(),
+        | None => match info.payload().downcast_ref::<String>() {
+            | Some(message) => message.clone(),
+            | None => "Box<dyn Any>".to_string(),
+        },
+    };
+    let location = info
+        .location()
+        .map(|l| format!("{}:{}:{}", l.file(), l.line(), l.column()))
+        .unwrap_or_else(|| "unknown".to_string());
+    let backtrace = Backtrace::capture();
+
+    match backtrace.status() {
+        | BacktraceStatus::Captured => tracing::error!(
+            panic.message = %message,
+            panic.location = %location,
+            panic.backtrace = %backtrace,
+            "Panic occurred"
+        ),
+        | _ => tracing::error!(
+            panic.message = %message,
+            panic.location = %location,
+            "Panic occurred"
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_subscriber::layer::SubscriberExt;
+
+    use super::*;
+    use crate::utils::testing::CapturedLogs;
+
+    #[test]
+    fn test_current_request_id_is_taken_from_enclosing_span() {
+        let subscriber = Registry::default().with(RequestIdLayer);
+        tracing::subscriber::with_default(subscriber, || {
+            assert_eq!(current_request_id(), None);
+
+            let request = tracing::info_span!("HTTP request", request_id = %"5f1a3c8e");
+            let _request = request.enter();
+            let query = tracing::info_span!("query");
+            let _query = query.enter();
+            assert_eq!(current_request_id().as_deref(), Some("5f1a3c8e"));
+        });
+    }
+
+    #[actix_rt::test]
+    async fn test_spawned_task_panics_are_logged() {
+        let logs = CapturedLogs::default();
+        let _guard = logs.set_default();
+
+        spawn_reported("startup", async { panic!("boom") });
+        actix_rt::time::sleep(std::time::Duration::from_millis(50)).await;
+
+        let output = logs.contents();
+        assert!(output.contains("Background task failed"), "{output}");
+        assert!(output.contains("task=\"startup\""), "{output}");
+    }
+
+    #[test]
+    fn test_panic_hook_emits_tracing_event() {
+        let logs = CapturedLogs::default();
+        let _guard = logs.set_default();
+
+        let previous = std::panic::take_hook();
+        std::panic::set_hook(Box::new(log_panic));
+        let result = std::panic::catch_unwind(|| panic!("boom {}", 42));
+        std::panic::set_hook(previous);
+
+        assert!(result.is_err());
+        let output = logs.contents();
+        assert!(output.contains("ERROR"));
+        assert!(output.contains("Panic occurred"));
+        assert!(output.contains("panic.message=boom 42"));
+        assert!(output.contains("panic.location=backend/src/utils/errors.rs"));
+    }
+}
@@ -1,7 +1,28 @@
-use tracing::subscriber::set_global_default;
+use std::{collections::HashMap, io::Write};
+
+use serde_json::json;
+use tracing::{
+    Event,
+    Metadata,
+    Subscriber,
+    span::{Attributes, Id},
+    subscriber::set_global_default,
+};
 use tracing_bunyan_formatter::{BunyanFormattingLayer, JsonStorageLayer};
 use tracing_log::LogTracer;
-use tracing_subscriber::{EnvFilter, Registry, layer::SubscriberExt};
+use tracing_subscriber::{
+    EnvFilter,
+    Layer,
+    Registry,
+    fmt::MakeWriter,
+    layer::{Context, SubscriberExt},
+    registry::LookupSpan,
+};
+
+use crate::utils::{build_info::BUILD_INFO, ecs::EcsLayer, errors::RequestIdLayer};
+
+/// Name `tracing_actix_web` gives the root span of every request.
+const REQUEST_SPAN_NAME: &str = "HTTP request";
 
 /// Initialize the logging system based on configuration
 ///
@@ -13,58 +34,274 @@ pub fn init_logging(
     environment: &str,
     log_level: &str,
     log_format: &str,
+    log_schema: &str,
 ) -> Result<(), Box<dyn std::error::Error>> {
     // Redirect all `log`'s events to our tracing subscriber
     LogTracer::init()?;
 
+    set_global_default(subscriber(
+        service_name,
+        environment,
+        log_level,
+        log_format,
+        log_schema,
+        std::io::stdout,
+    ))?;
+
+    // Log initialization info
+    tracing::info!(
+        service_name = %service_name,
+        environment = %environment,
+        log_level = %log_level,
+        log_format = %log_format,
+        log_schema = %log_schema,
+        "Logging initialized"
+    );
+
+    Ok(())
+}
+
+/// Build the subscriber [`init_logging`] installs, writing events to `writer`.
+fn subscriber<W>(
+    service_name: &str,
+    environment: &str,
+    log_level: &str,
+    log_format: &str,
+    log_schema: &str,
+    writer: W,
+) -> Box<dyn Subscriber + Send + Sync>
+where
+    W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
     // Set up the env filter
     let env_filter =
         EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(log_level));
+    // Keeps request ids for tagging error reports, whatever the format
+    let registry = Registry::default().with(env_filter).with(RequestIdLayer);
 
     match log_format {
+        | "json" if log_schema == "ecs" => {
+            // Elastic Common Schema field names, for dashboards shared across services
+            let subscriber = registry.with(EcsLayer::new(service_name, environment, writer));
+
+            Box::new(subscriber)
+        }
         | "json" => {
-            // JSON format for Kibana
-            let formatting_layer =
-                BunyanFormattingLayer::new(service_name.to_string(), std::io::stdout);
+            // JSON format for Kibana, tagging every event with the running build
+            let default_fields = HashMap::from([
+                ("version".to_string(), json!(BUILD_INFO.version)),
+                ("git_sha".to_string(), json!(BUILD_INFO.git_sha)),
+            ]);
+            let formatting_layer = BunyanFormattingLayer::with_default_fields(
+                service_name.to_string(),
+                writer,
+                default_fields,
+            );
 
-            let subscriber = Registry::default()
-                .with(env_filter)
+            let subscriber = registry
                 .with(JsonStorageLayer)
-                .with(formatting_layer);
+                .with(WithoutRequestSpanLines(formatting_layer));
 
-            set_global_default(subscriber)?;
+            Box::new(subscriber)
+        }
+        | "json-compact" => {
+            // Flat JSON lines for log shippers that don't understand the Bunyan schema
+            let subscriber = registry.with(compact_json_layer(writer));
+
+            Box::new(subscriber)
         }
         | _ => {
             // Pretty format for development/debugging (default for any non-json value)
-            let subscriber = Registry::default()
-                .with(env_filter)
-                .with(tracing_subscriber::fmt::layer());
+            let subscriber = registry.with(tracing_subscriber::fmt::layer().with_writer(writer));
 
-            set_global_default(subscriber)?;
+            Box::new(subscriber)
         }
     }
+}
 
-    // Log initialization info
-    tracing::info!(
-        service_name = %service_name,
-        environment = %environment,
-        log_level = %log_level,
-        log_format = %log_format,
-        "Logging initialized"
-    );
+/// Bunyan layer that doesn't log the `START`/`END` lines of request spans.
+///
+/// [`AccessLog`](crate::middleware::AccessLog) logs each request once, sampled, so the request
+/// span only lends its fields (such as `request_id`) to the events logged inside it.
+struct WithoutRequestSpanLines<L>(L);
 
-    Ok(())
+impl<S, L> Layer<S> for WithoutRequestSpanLines<L>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    L: Layer<S>,
+{
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        self.0.on_event(event, ctx);
+    }
+
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        if !is_request_span(attrs.metadata()) {
+            self.0.on_new_span(attrs, id, ctx);
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        if !ctx.metadata(&id).is_some_and(is_request_span) {
+            self.0.on_close(id, ctx);
+        }
+    }
+}
+
+fn is_request_span(metadata: &Metadata<'_>) -> bool {
+    metadata.name() == REQUEST_SPAN_NAME && metadata.target().starts_with("tracing_actix_web")
+}
+
+/// One JSON object per event, with the event's fields next to `timestamp`, `level` and
+/// `target` instead of nested under `fields`, and the running build's `version` and `git_sha`.
+fn compact_json_layer<S, W>(writer: W) -> impl Layer<S>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    W: for<'a> MakeWriter<'a> + Send + Sync + 'static,
+{
+    tracing_subscriber::fmt::layer()
+        .json()
+        .flatten_event(true)
+        .with_writer(BuildFields::new(writer))
+}
+
+/// Writer prepending the running build's `version` and `git_sha` to every JSON object written.
+///
+/// The fmt layer writes each event as a single object, so the fields are spliced in after its
+/// opening brace.
+struct BuildFields<W> {
+    inner: W,
+    prefix: String,
+}
+
+impl<W> BuildFields<W> {
+    fn new(inner: W) -> Self {
+        let prefix = format!(
+            "{{\"version\":{},\"git_sha\":{},",
+            json!(BUILD_INFO.version),
+            json!(BUILD_INFO.git_sha)
+        );
+        Self {
+            inner,
+            prefix,
+        }
+    }
+}
+
+impl<'a, W: MakeWriter<'a>> MakeWriter<'a> for BuildFields<W> {
+    type Writer = BuildFieldsWriter<'a, W::Writer>;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        BuildFieldsWriter {
+            inner: self.inner.make_writer(),
+            prefix: &self.prefix,
+        }
+    }
+}
+
+struct BuildFieldsWriter<'a, W> {
+    inner: W,
+    prefix: &'a str,
+}
+
+impl<W: Write> Write for BuildFieldsWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        match buf.strip_prefix(b"{") {
+            | Some(rest) => {
+                self.inner.write_all(self.prefix.as_bytes())?;
+                self.inner.write_all(rest)?;
+                Ok(buf.len())
+            }
+            | None => self.inner.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use serde_json::Value;
+
     use super::*;
+    use crate::utils::testing::CapturedLogs;
+
+    /// Log one event through the subscriber built for `log_format`, returning the output.
+    fn log_with(log_format: &str, log_schema: &str) -> String {
+        let logs = CapturedLogs::default();
+        let subscriber =
+            subscriber("test-service", "test", "info", log_format, log_schema, logs.clone());
+
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::info!(user = "ada", "Signed in");
+        });
+        logs.contents()
+    }
 
     #[test]
-    fn test_init_logging_json() {
-        let result = init_logging("test-service", "test", "info", "json");
-        // We can't test much here as logging can only be initialized once per process
-        // but we can at least verify it doesn't panic
-        assert!(result.is_ok() || result.is_err());
+    fn test_json_lines_carry_build_info() {
+        for log_format in ["json", "json-compact"] {
+            let output = log_with(log_format, "bunyan");
+            let line: Value = serde_json::from_str(output.trim()).unwrap();
+            assert_eq!(line["version"], BUILD_INFO.version, "{log_format}");
+            assert_eq!(line["git_sha"], BUILD_INFO.git_sha, "{log_format}");
+        }
+    }
+
+    #[test]
+    fn test_compact_json_is_flat() {
+        let output = log_with("json-compact", "bunyan");
+
+        assert_eq!(output.lines().count(), 1);
+        let line: Value = serde_json::from_str(output.trim()).unwrap();
+        assert_eq!(line["level"], "INFO");
+        assert_eq!(line["message"], "Signed in");
+        assert_eq!(line["user"], "ada");
+        assert!(line.get("fields").is_none());
+    }
+
+    #[actix_rt::test]
+    async fn test_requests_are_logged_once_by_the_access_log() {
+        use actix_web::{App, HttpResponse, web};
+        use tracing_actix_web::TracingLogger;
+
+        use crate::middleware::AccessLog;
+
+        let logs = CapturedLogs::default();
+        let subscriber = subscriber("test-service", "test", "info", "json", "bunyan", logs.clone());
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let app = actix_web::test::init_service(
+            App::new()
+                .wrap(AccessLog::new(0.0))
+                .wrap(TracingLogger::default())
+                .route("/ok", web::get().to(HttpResponse::Ok))
+                .route("/fail", web::get().to(HttpResponse::InternalServerError)),
+        )
+        .await;
+        for uri in ["/ok", "/fail"] {
+            let req = actix_web::test::TestRequest::get().uri(uri).to_request();
+            actix_web::test::call_service(&app, req).await;
+        }
+
+        let lines: Vec<Value> = logs
+            .contents()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 1, "{lines:?}");
+        assert_eq!(lines[0]["http.status_code"], 500);
+        assert!(lines[0]["request_id"].is_string());
+    }
+
+    #[test]
+    fn test_format_selects_subscriber() {
+        let ecs: Value = serde_json::from_str(log_with("json", "ecs").trim()).unwrap();
+        assert_eq!(ecs["service.name"], "test-service");
+
+        let pretty = log_with("pretty", "bunyan");
+        assert!(pretty.contains("Signed in"));
+        assert!(serde_json::from_str::<Value>(pretty.trim()).is_err());
     }
 }
@@ -1,15 +1,22 @@
-use std::env;
 use std::str::FromStr;
 
+use crate::config;
+
+pub mod build_info;
+pub mod database;
+pub mod ecs;
+pub mod errors;
 pub mod logging;
+pub mod routes;
+#[cfg(test)]
+pub mod testing;
 
-/// Get an environment variable or return a default value
+/// Get an environment variable, falling back to the config file, or return a default value
 pub fn env_or_default<T>(key: &str, default: T) -> T
 where
     T: FromStr,
 {
-    env::var(key)
-        .ok()
+    config::lookup(key)
         .and_then(|val| val.parse::<T>().ok())
         .unwrap_or(default)
 }
@@ -0,0 +1,132 @@
+use actix_web::http::Method;
+
+/// Largest edit distance at which a registered route is still suggested for an unknown path.
+const MAX_SUGGESTION_DISTANCE: usize = 4;
+
+/// Method and path pattern of every registered route.
+///
+/// Actix doesn't expose its routing table, so [`router::mount`](crate::router::mount) records
+/// each route as it mounts it. The fallback handler uses the table to tell unknown paths (`404`)
+/// from unsupported methods (`405`) and to suggest the closest route for typos.
+#[derive(Debug)]
+pub struct RouteTable {
+    routes: Vec<(Method, String)>,
+    suggestions: bool,
+}
+
+impl RouteTable {
+    pub fn new(routes: Vec<(Method, String)>, suggestions: bool) -> Self {
+        Self {
+            routes,
+            suggestions,
+        }
+    }
+
+    /// Registered `(method, pattern)` pairs.
+    #[cfg(test)]
+    pub fn routes(&self) -> &[(Method, String)] {
+        &self.routes
+    }
+
+    /// Methods registered for `path`, empty if no route matches it.
+    pub fn allowed_methods(&self, path: &str) -> Vec<Method> {
+        let mut methods = Vec::new();
+        for (method, pattern) in &self.routes {
+            if matches_pattern(pattern, path) && !methods.contains(method) {
+                methods.push(method.clone());
+            }
+        }
+        methods
+    }
+
+    /// The registered pattern closest to `path`, if suggestions are enabled and one is close.
+    pub fn suggest(&self, path: &str) -> Option<&str> {
+        if !self.suggestions {
+            return None;
+        }
+
+        self.routes
+            .iter()
+            .map(|(_, pattern)| (levenshtein(pattern, path), pattern.as_str()))
+            .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, pattern)| pattern)
+    }
+}
+
+/// Whether `path` matches `pattern`, where a `{name}` segment matches any single segment.
+fn matches_pattern(pattern: &str, path: &str) -> bool {
+    let mut pattern_segments = pattern.split('/');
+    let mut path_segments = path.split('/');
+
+    loop {
+        match (pattern_segments.next(), path_segments.next()) {
+            | (None, None) => return true,
+            | (Some(expected), Some(actual)) => {
+                let is_param = expected.starts_with('{') && expected.ends_with('}');
+                if !(expected == actual || (is_param && !actual.is_empty())) {
+                    return false;
+                }
+            }
+            | _ => return false,
+        }
+    }
+}
+
+/// Number of single character insertions, deletions or substitutions turning `a` into `b`.
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(suggestions: bool) -> RouteTable {
+        RouteTable::new(
+            vec![
+                (Method::GET, "/health/live".to_string()),
+                (Method::GET, "/templates/{id}".to_string()),
+                (Method::PUT, "/templates/{id}".to_string()),
+            ],
+            suggestions,
+        )
+    }
+
+    #[test]
+    fn test_allowed_methods() {
+        let table = table(true);
+        assert_eq!(table.allowed_methods("/health/live"), vec![Method::GET]);
+        assert_eq!(table.allowed_methods("/templates/42"), vec![Method::GET, Method::PUT]);
+        assert!(table.allowed_methods("/templates/").is_empty());
+        assert!(table.allowed_methods("/templates/42/versions").is_empty());
+        assert!(table.allowed_methods("/health").is_empty());
+    }
+
+    #[test]
+    fn test_suggests_closest_route() {
+        assert_eq!(table(true).suggest("/health/lvie"), Some("/health/live"));
+        assert_eq!(table(true).suggest("/helth/live"), Some("/health/live"));
+        assert_eq!(table(true).suggest("/something/else/entirely"), None);
+        assert_eq!(table(false).suggest("/health/lvie"), None);
+    }
+
+    #[test]
+    fn test_levenshtein() {
+        assert_eq!(levenshtein("kitten", "sitting"), 3);
+        assert_eq!(levenshtein("", "abc"), 3);
+        assert_eq!(levenshtein("same", "same"), 0);
+    }
+}
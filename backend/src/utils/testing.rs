@@ -0,0 +1,116 @@
+use std::{
+    borrow::Cow,
+    error::Error as StdError,
+    fmt,
+    io::Write,
+    sync::{Arc, Mutex},
+};
+
+use sqlx::error::{DatabaseError, ErrorKind};
+use tracing::subscriber::DefaultGuard;
+use tracing_subscriber::fmt::MakeWriter;
+
+/// In-memory log sink for asserting on tracing output in tests.
+#[derive(Clone, Default)]
+pub struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+impl CapturedLogs {
+    /// Install a plain-text subscriber writing into this sink for the current thread.
+    pub fn set_default(&self) -> DefaultGuard {
+        let subscriber = tracing_subscriber::fmt()
+            .with_writer(self.clone())
+            .with_ansi(false)
+            .with_max_level(tracing::Level::TRACE)
+            .finish();
+        tracing::subscriber::set_default(subscriber)
+    }
+
+    pub fn contents(&self) -> String {
+        String::from_utf8_lossy(&self.0.lock().unwrap()).into_owned()
+    }
+}
+
+impl Write for CapturedLogs {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+impl<'a> MakeWriter<'a> for CapturedLogs {
+    type Writer = Self;
+
+    fn make_writer(&'a self) -> Self::Writer {
+        self.clone()
+    }
+}
+
+/// MySQL driver error with a given error `number`, to build `sqlx::Error::Database` values
+/// without a database. The SQLSTATE and error kind are derived from the number the way MySQL and
+/// sqlx report them, e.g. `1062` (duplicate entry) is SQLSTATE `23000` and a unique violation.
+#[derive(Debug)]
+pub struct FakeDatabaseError {
+    pub number: u16,
+    pub constraint: Option<&'static str>,
+}
+
+pub fn database_error(number: u16, constraint: Option<&'static str>) -> sqlx::Error {
+    sqlx::Error::Database(Box::new(FakeDatabaseError {
+        number,
+        constraint,
+    }))
+}
+
+impl fmt::Display for FakeDatabaseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "fake database error {}", self.number)
+    }
+}
+
+impl StdError for FakeDatabaseError {}
+
+impl DatabaseError for FakeDatabaseError {
+    fn message(&self) -> &str {
+        "fake database error"
+    }
+
+    fn code(&self) -> Option<Cow<'_, str>> {
+        let sqlstate = match self.number {
+            | 1048 | 1062 | 1451 | 1452 => "23000",
+            | 1054 => "42S22",
+            | 1146 => "42S02",
+            | 1213 => "40001",
+            | _ => "HY000",
+        };
+        Some(Cow::Borrowed(sqlstate))
+    }
+
+    fn constraint(&self) -> Option<&str> {
+        self.constraint
+    }
+
+    fn as_error(&self) -> &(dyn StdError + Send + Sync + 'static) {
+        self
+    }
+
+    fn as_error_mut(&mut self) -> &mut (dyn StdError + Send + Sync + 'static) {
+        self
+    }
+
+    fn into_error(self: Box<Self>) -> Box<dyn StdError + Send + Sync + 'static> {
+        self
+    }
+
+    fn kind(&self) -> ErrorKind {
+        match self.number {
+            | 1062 => ErrorKind::UniqueViolation,
+            | 1451 | 1452 => ErrorKind::ForeignKeyViolation,
+            | 1048 => ErrorKind::NotNullViolation,
+            | 3819 => ErrorKind::CheckViolation,
+            | _ => ErrorKind::Other,
+        }
+    }
+}